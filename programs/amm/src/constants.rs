@@ -0,0 +1,48 @@
+// Seconds without a price observation after which cranking update_observation is rewarded
+pub const OBSERVATION_IDLE_SECS: i64 = 600;
+// Lamports paid from the crank vault to whoever refreshes an idle pool's oracle
+pub const OBSERVATION_CRANK_REWARD: u64 = 10_000;
+
+// Internal pool shares per LP token base unit at the first deposit
+pub const LP_SHARE_SCALE: u128 = 1_000_000_000;
+// LP token base units of the first deposit that are never minted, locking their share of the pool
+pub const MINIMUM_LIQUIDITY: u64 = 1_000;
+
+// Window the PriceOracle TWAP is computed over
+pub const ORACLE_TWAP_WINDOW_SECS: i64 = 30 * 60;
+
+// Config::risk_flags bits, set at initialization from the pooled mints
+pub const RISK_FREEZE_AUTHORITY_X: u8 = 1 << 0; // mint_x has a freeze authority that can freeze vault_x
+pub const RISK_FREEZE_AUTHORITY_Y: u8 = 1 << 1; // mint_y has a freeze authority that can freeze vault_y
+pub const RISK_PERMANENT_DELEGATE_X: u8 = 1 << 2; // mint_x has a permanent delegate that can move vault_x funds
+pub const RISK_PERMANENT_DELEGATE_Y: u8 = 1 << 3; // mint_y has a permanent delegate that can move vault_y funds
+pub const RISK_CLOSE_AUTHORITY_X: u8 = 1 << 4; // mint_x can be closed and recreated by its close authority
+pub const RISK_CLOSE_AUTHORITY_Y: u8 = 1 << 5; // mint_y can be closed and recreated by its close authority
+
+// Delay between proposing and executing an insurance fund payout
+pub const INSURANCE_TIMELOCK_SECS: i64 = 2 * 24 * 60 * 60;
+
+// Smallest weight either side of an LBP can have, in basis points
+pub const MIN_LBP_WEIGHT: u16 = 100;
+
+// Time stakers have to claim an incentive after its schedule ends before the funder can reclaim the rest
+pub const INCENTIVE_CLAIM_WINDOW_SECS: i64 = 30 * 24 * 60 * 60;
+
+// Time an LP has to use a matured withdraw request before it goes stale and has to be made again
+pub const WITHDRAW_CLAIM_WINDOW_SECS: i64 = 2 * 24 * 60 * 60;
+
+// Time a pool has to go without a price observation before an empty pool can be garbage collected
+pub const GC_INACTIVITY_SECS: i64 = 30 * 24 * 60 * 60;
+// Share of a garbage collected pool's rent paid to the caller, in basis points, the treasury gets the rest
+pub const GC_CALLER_SHARE_BPS: u16 = 5_000;
+
+// Loss beyond the pool fee that harvest_fees accepts against the oracle TWAP, in basis points
+pub const HARVEST_MAX_SLIPPAGE_BPS: u16 = 100;
+
+// Highest share of a reserve in basis points the authority can let a pool's strategy hold
+pub const MAX_DEPLOYED_BPS: u16 = 5_000;
+// Delay between setting a strategy or raising its limit and deploy_idle lending under it
+pub const STRATEGY_TIMELOCK_SECS: i64 = 2 * 24 * 60 * 60;
+
+// Instruction discriminator Token-2022 invokes a mint's transfer hook with, from the SPL transfer hook interface
+pub const TRANSFER_HOOK_EXECUTE_DISCRIMINATOR: &[u8] = &[105, 37, 101, 197, 75, 251, 102, 26];
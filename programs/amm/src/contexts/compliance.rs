@@ -0,0 +1,89 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{freeze_account, thaw_account, FreezeAccount, ThawAccount, Mint, TokenAccount, TokenInterface};
+
+use crate::state::Config;
+use crate::errors::AmmError;
+use crate::events::LpAccountFreezeChanged;
+
+// Freezing and thawing individual LP token accounts in compliant-mode pools, with the LP mint's
+// freeze authority held by the config PDA
+#[derive(Accounts)]
+pub struct ComplianceFreeze<'info> {
+    pub authority: Signer<'info>,
+    pub mint_x: InterfaceAccount<'info, Mint>,
+    pub mint_y: InterfaceAccount<'info, Mint>,
+    #[account(
+        has_one = mint_x,
+        has_one = mint_y,
+        constraint = config.authority == Some(authority.key()) @ AmmError::Unauthorized,
+        constraint = !config.immutable @ AmmError::ConfigImmutable,
+        constraint = config.compliant @ AmmError::NotCompliant,
+        seeds = [
+            b"config",
+            mint_x.key().to_bytes().as_ref(),
+            mint_y.key().to_bytes().as_ref(),
+            config.seed.to_le_bytes().as_ref()
+        ],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        seeds = [b"lp", config.key().as_ref()],
+        bump = config.lp_bump,
+    )]
+    pub mint_lp: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        token::mint = mint_lp,
+    )]
+    pub lp_account: InterfaceAccount<'info, TokenAccount>,
+
+    // Program of the LP mint
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> ComplianceFreeze<'info> {
+    pub fn set_frozen(&mut self, frozen: bool) -> Result<()> {
+        let mint_x = self.mint_x.key().to_bytes();
+        let mint_y = self.mint_y.key().to_bytes();
+        let seed = self.config.seed.to_le_bytes();
+
+        let seeds = [
+            b"config",
+            mint_x.as_ref(),
+            mint_y.as_ref(),
+            seed.as_ref(),
+            &[self.config.config_bump]
+        ];
+
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_program = self.token_program.to_account_info();
+        let account = self.lp_account.to_account_info();
+        let mint = self.mint_lp.to_account_info();
+        let authority = self.config.to_account_info();
+
+        match frozen {
+            true => freeze_account(CpiContext::new_with_signer(
+                cpi_program,
+                FreezeAccount { account, mint, authority },
+                signer_seeds,
+            ))?,
+            false => thaw_account(CpiContext::new_with_signer(
+                cpi_program,
+                ThawAccount { account, mint, authority },
+                signer_seeds,
+            ))?,
+        }
+
+        emit!(LpAccountFreezeChanged {
+            config: self.config.key(),
+            lp_account: self.lp_account.key(),
+            owner: self.lp_account.owner,
+            frozen,
+            authority: self.authority.key(),
+        });
+
+        Ok(())
+    }
+}
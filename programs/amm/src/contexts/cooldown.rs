@@ -0,0 +1,78 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount};
+
+use crate::state::{Config, WithdrawRequest};
+use crate::errors::AmmError;
+
+#[derive(Accounts)]
+pub struct RequestWithdraw<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub mint_x: InterfaceAccount<'info, Mint>,
+    pub mint_y: InterfaceAccount<'info, Mint>,
+    #[account(
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [
+            b"config",
+            mint_x.key().to_bytes().as_ref(),
+            mint_y.key().to_bytes().as_ref(),
+            config.seed.to_le_bytes().as_ref()
+        ],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        seeds = [b"lp", config.key().as_ref()],
+        bump = config.lp_bump,
+    )]
+    pub mint_lp: InterfaceAccount<'info, Mint>,
+    #[account(
+        associated_token::mint = mint_lp,
+        associated_token::authority = owner,
+    )]
+    pub owner_ata_lp: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = WithdrawRequest::LEN,
+        seeds = [b"withdraw_request", config.key().as_ref(), owner.key().as_ref()],
+        bump
+    )]
+    pub withdraw_request: Account<'info, WithdrawRequest>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> RequestWithdraw<'info> {
+    // Starts the cooldown for `lp_amount` LP tokens, replacing any earlier request and restarting its timer
+    pub fn request(&mut self, lp_amount: u64, bumps: &RequestWithdrawBumps) -> Result<()> {
+        require!(lp_amount > 0, AmmError::InvalidAmount);
+        require!(self.config.withdraw_cooldown_secs > 0, AmmError::InvalidConfig);
+        require!(lp_amount <= self.owner_ata_lp.amount, AmmError::InsufficientBalance);
+
+        self.withdraw_request.set_inner(WithdrawRequest {
+            config: self.config.key(),
+            owner: self.owner.key(),
+            lp_amount,
+            unlock_ts: Clock::get()?.unix_timestamp + self.config.withdraw_cooldown_secs,
+            bump: bumps.withdraw_request,
+        });
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct CloseWithdrawRequest<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        close = owner,
+        has_one = owner,
+        seeds = [b"withdraw_request", withdraw_request.config.as_ref(), owner.key().as_ref()],
+        bump = withdraw_request.bump,
+    )]
+    pub withdraw_request: Account<'info, WithdrawRequest>,
+}
@@ -1,19 +1,29 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_option::COption;
 use anchor_spl::associated_token::AssociatedToken;
-use anchor_spl::token::{transfer_checked, mint_to, MintTo, TransferChecked, Token, Mint, TokenAccount};
+use anchor_spl::token_interface::{
+    freeze_account, mint_to, thaw_account, transfer_checked, FreezeAccount, MintTo, ThawAccount, TransferChecked,
+    Mint, TokenAccount, TokenInterface,
+};
 
-use constant_product_curve::ConstantProduct;
-
-use crate::state::Config;
+use crate::state::{Config, LaunchDeposit, LpHolders};
 use crate::errors::AmmError;
+use crate::events::log_slippage;
+use crate::quote::deposit_amounts;
 
 #[derive(Accounts)]
 pub struct Deposit<'info> {
+    // The owner of the token accounts, or a delegate approved on both of them
     #[account(mut)]
     pub lp_provider: Signer<'info>,
-    pub mint_x: Account<'info, Mint>,
-    pub mint_y: Account<'info, Mint>,
+    /// CHECK: only used as the authority of the provider's token accounts, which receive the LP tokens
+    pub owner: UncheckedAccount<'info>,
+    #[account(mint::token_program = token_program_x)]
+    pub mint_x: InterfaceAccount<'info, Mint>,
+    #[account(mint::token_program = token_program_y)]
+    pub mint_y: InterfaceAccount<'info, Mint>,
     #[account(
+        mut,
         has_one = mint_x,
         has_one = mint_y,
         seeds = [
@@ -32,83 +42,163 @@ pub struct Deposit<'info> {
         mint::decimals = 6,
         mint::authority = config,
     )]
-    pub lp_mint: Account<'info, Mint>,
+    pub lp_mint: InterfaceAccount<'info, Mint>,
     #[account(
         mut,
-        associated_token::mint = mint_x,
-        associated_token::authority = config,
+        seeds = [b"vault_x", config.key().as_ref()],
+        bump = config.vault_x_bump,
     )]
-    pub vault_x: Account<'info, TokenAccount>,
+    pub vault_x: InterfaceAccount<'info, TokenAccount>,
     #[account(
         mut,
-        associated_token::mint = mint_y,
-        associated_token::authority = config,
+        seeds = [b"vault_y", config.key().as_ref()],
+        bump = config.vault_y_bump,
     )]
-    pub vault_y: Account<'info, TokenAccount>,
+    pub vault_y: InterfaceAccount<'info, TokenAccount>,
     #[account(
         mut,
         associated_token::mint = mint_x,
-        associated_token::authority = lp_provider,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program_x,
     )]
-    pub lp_provider_ata_x: Account<'info, TokenAccount>,
+    pub lp_provider_ata_x: InterfaceAccount<'info, TokenAccount>,
     #[account(
         mut,
         associated_token::mint = mint_y,
-        associated_token::authority = lp_provider,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program_y,
     )]
-    pub lp_provider_ata_y: Account<'info, TokenAccount>,
+    pub lp_provider_ata_y: InterfaceAccount<'info, TokenAccount>,
     #[account(
         init_if_needed,
         payer = lp_provider,
         associated_token::mint = lp_mint,
-        associated_token::authority = lp_provider,
+        associated_token::authority = owner,
     )]
-    pub lp_provider_ata_lp: Account<'info, TokenAccount>,
-    pub token_program: Program<'info, Token>,
+    pub lp_provider_ata_lp: InterfaceAccount<'info, TokenAccount>,
+    // The owner's running total against the per-wallet launch cap, required while the cap applies
+    #[account(
+        init_if_needed,
+        payer = lp_provider,
+        space = LaunchDeposit::LEN,
+        seeds = [b"launch_deposit", config.key().as_ref(), owner.key().as_ref()],
+        bump,
+    )]
+    pub launch_deposit: Option<Account<'info, LaunchDeposit>>,
+    // Required when the pool tracks LP holders
+    #[account(
+        mut,
+        seeds = [b"lp_holders", lp_mint.key().as_ref()],
+        bump = lp_holders.load()?.bump,
+    )]
+    pub lp_holders: Option<AccountLoader<'info, LpHolders>>,
+    // Program of the LP mint
+    pub token_program: Interface<'info, TokenInterface>,
+    pub token_program_x: Interface<'info, TokenInterface>,
+    pub token_program_y: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
     pub associated_token_program: Program<'info, AssociatedToken>,
 }
 
 impl<'info> Deposit<'info> {
-    pub fn deposit(&mut self, lp_amount: u64, max_x: u64, max_y: u64) -> Result<()> {
-        require!(lp_amount > 0, AmmError::InvalidAmount);
-        require!(!self.config.locked, AmmError::AMMLocked);
+    // Deposits up to `max_x` and `max_y` at the pool's ratio, see quote::deposit_amounts. The first
+    // deposit sets the price with both amounts.
+    pub fn deposit(&mut self, max_x: u64, max_y: u64, min_lp: u64, bumps: &DepositBumps) -> Result<()> {
+        require!(max_x > 0 && max_y > 0, AmmError::InvalidAmount);
+        require!(!self.config.is_locked(Clock::get()?.unix_timestamp), AmmError::AMMLocked);
 
-        let (x, y) = match self.lp_mint.supply == 0 && self.vault_x.amount == 0 && self.vault_y.amount == 0 {
-            true => (max_x, max_y),
-            false => {
-                let amounts = ConstantProduct::xy_deposit_amounts_from_l(
-                    self.vault_x.amount,
-                    self.vault_y.amount,
-                    self.lp_mint.supply,
-                    lp_amount,
-                    6,
-                ).map_err(|_| AmmError::InvalidAmount)?; // Handle error properly
-                (amounts.x, amounts.y) 
-            },
-        };
+        let (reserve_x, reserve_y) = self.config.reserves(self.vault_x.amount, self.vault_y.amount)?;
+
+        // New LPs also buy into the fees already earned so they don't dilute existing LPs. Without
+        // shares the deposit is the first one, tokens sent to the vaults before it go to its LP.
+        let quote = deposit_amounts(&self.config.pool_state(reserve_x, reserve_y, self.lp_mint.supply), max_x, max_y)
+            .ok_or(AmmError::InvalidAmount)?;
+        let (x, y, lp_amount) = (quote.amount_x, quote.amount_y, quote.lp_amount);
+
+        if lp_amount < min_lp {
+            log_slippage(self.config.key(), self.lp_provider.key(), self.lp_mint.key(), lp_amount, min_lp);
+        }
+        require!(lp_amount >= min_lp, AmmError::SlippageExceeded);
+        require!(lp_amount > 0, AmmError::InvalidAmount);
+        require!(
+            self.config.max_lp_supply == 0
+                || self.lp_mint.supply.saturating_add(lp_amount) <= self.config.max_lp_supply,
+            AmmError::TvlCapExceeded
+        );
+        self.track_launch_deposit(lp_amount, bumps)?;
+        self.check_delegation(x, y)?;
 
-        require!(max_x >= x, AmmError::InsufficientTokenX);
-        require!(max_y >= y, AmmError::InsufficientTokenY);
+        self.config.lp_fees_x = self.config.lp_fees_x.checked_add(quote.fee_x).ok_or(AmmError::InvalidAmount)?;
+        self.config.lp_fees_y = self.config.lp_fees_y.checked_add(quote.fee_y).ok_or(AmmError::InvalidAmount)?;
+        self.config.total_shares = self.config.total_shares.checked_add(quote.shares).ok_or(AmmError::InvalidAmount)?;
 
         self.deposit_token(true, x)?;
         self.deposit_token(false, y)?;
+        if self.config.soulbound && self.lp_provider_ata_lp.is_frozen() {
+            self.set_lp_frozen(false)?;
+        }
         self.mint_lp_tokens(lp_amount)?;
+        if self.config.soulbound {
+            self.set_lp_frozen(true)?;
+        }
+        if self.config.track_lp_holders {
+            let lp_holders = self.lp_holders.as_ref().ok_or(AmmError::MissingLpHolders)?;
+            lp_holders.load_mut()?.credit(self.owner.key(), lp_amount)?;
+        }
 
         Ok(())
     }
 
-    fn deposit_token(&mut self, is_x: bool, amount: u64) -> Result<()> {
-        let cpi_program = self.token_program.to_account_info();
+    fn track_launch_deposit(&mut self, lp_amount: u64, bumps: &DepositBumps) -> Result<()> {
+        if !self.config.launch_cap_active(Clock::get()?.unix_timestamp) {
+            return Ok(());
+        }
+
+        let config = self.config.key();
+        let owner = self.owner.key();
+        let launch_deposit = self.launch_deposit.as_mut().ok_or(AmmError::MissingLaunchDeposit)?;
+
+        if launch_deposit.config == Pubkey::default() {
+            launch_deposit.set_inner(LaunchDeposit {
+                config,
+                owner,
+                lp_minted: 0,
+                bump: bumps.launch_deposit.ok_or(AmmError::MissingLaunchDeposit)?,
+            });
+        }
+
+        let lp_minted = launch_deposit.lp_minted.checked_add(lp_amount).ok_or(AmmError::InvalidAmount)?;
+        require!(lp_minted <= self.config.launch_deposit_cap, AmmError::LaunchCapExceeded);
+        launch_deposit.lp_minted = lp_minted;
+
+        Ok(())
+    }
+
+    // Custodians and vault programs deposit for a user through a delegate approval covering the amounts
+    fn check_delegation(&self, x: u64, y: u64) -> Result<()> {
+        if self.lp_provider.key() == self.owner.key() {
+            return Ok(());
+        }
 
-        let (cpi_accounts, mint_decimals) = match is_x {
-            true => (TransferChecked {
+        for (ata, amount) in [(&self.lp_provider_ata_x, x), (&self.lp_provider_ata_y, y)] {
+            require!(
+                ata.delegate == COption::Some(self.lp_provider.key()) && ata.delegated_amount >= amount,
+                AmmError::InvalidDelegate
+            );
+        }
+
+        Ok(())
+    }
+
+    fn deposit_token(&mut self, is_x: bool, amount: u64) -> Result<()> {
+        let (cpi_program, cpi_accounts, mint_decimals) = match is_x {
+            true => (self.token_program_x.to_account_info(), TransferChecked {
                     from: self.lp_provider_ata_x.to_account_info(),
                     mint: self.mint_x.to_account_info(),
                     to: self.vault_x.to_account_info(),
                     authority: self.lp_provider.to_account_info(),
                 }, self.mint_x.decimals),
-            false => (TransferChecked {
+            false => (self.token_program_y.to_account_info(), TransferChecked {
                     from: self.lp_provider_ata_y.to_account_info(),
                     mint: self.mint_y.to_account_info(),
                     to: self.vault_y.to_account_info(),
@@ -153,4 +243,44 @@ impl<'info> Deposit<'info> {
         
         Ok(())
     }
-}
\ No newline at end of file
+
+    // Soulbound pools keep LP accounts frozen, so they are thawed only for the duration of a mint or burn
+    fn set_lp_frozen(&mut self, frozen: bool) -> Result<()> {
+        let cpi_program = self.token_program.to_account_info();
+
+        let mint_x = self.mint_x.key().to_bytes();
+        let mint_y = self.mint_y.key().to_bytes();
+        let seed = self.config.seed.to_le_bytes();
+
+        let seeds = [
+            b"config",
+            mint_x.as_ref(),
+            mint_y.as_ref(),
+            seed.as_ref(),
+            &[self.config.config_bump]
+        ];
+
+        let signer_seeds = &[&seeds[..]];
+
+        match frozen {
+            true => {
+                let cpi_accounts = FreezeAccount {
+                    account: self.lp_provider_ata_lp.to_account_info(),
+                    mint: self.lp_mint.to_account_info(),
+                    authority: self.config.to_account_info(),
+                };
+                freeze_account(CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds))?;
+            }
+            false => {
+                let cpi_accounts = ThawAccount {
+                    account: self.lp_provider_ata_lp.to_account_info(),
+                    mint: self.lp_mint.to_account_info(),
+                    authority: self.config.to_account_info(),
+                };
+                thaw_account(CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds))?;
+            }
+        }
+
+        Ok(())
+    }
+}
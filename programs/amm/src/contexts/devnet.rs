@@ -0,0 +1,175 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::{mint_to, MintTo, Token};
+use anchor_spl::token_interface::{Mint, TokenAccount};
+
+use crate::program::Amm;
+use crate::state::{GlobalConfig, ProtocolStats};
+use crate::errors::AmmError;
+
+// Devnet only: creates two test mints owned by the payer, mints the reserves and sets up a pool
+// holding them, routed through initialize and deposit so the pool is exactly what those would build.
+// The pool accounts are checked by those instructions.
+#[derive(Accounts)]
+pub struct SeedTestPool<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [b"global"],
+        bump = global_config.bump,
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    #[account(
+        mut,
+        seeds = [b"stats"],
+        bump = protocol_stats.bump,
+    )]
+    pub protocol_stats: Account<'info, ProtocolStats>,
+    #[account(
+        init,
+        payer = payer,
+        mint::decimals = 6,
+        mint::authority = payer,
+        mint::token_program = token_program,
+    )]
+    pub mint_x: InterfaceAccount<'info, Mint>,
+    #[account(
+        init,
+        payer = payer,
+        mint::decimals = 6,
+        mint::authority = payer,
+        mint::token_program = token_program,
+    )]
+    pub mint_y: InterfaceAccount<'info, Mint>,
+    #[account(
+        init,
+        payer = payer,
+        associated_token::mint = mint_x,
+        associated_token::authority = payer,
+        associated_token::token_program = token_program,
+    )]
+    pub payer_ata_x: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = payer,
+        associated_token::mint = mint_y,
+        associated_token::authority = payer,
+        associated_token::token_program = token_program,
+    )]
+    pub payer_ata_y: InterfaceAccount<'info, TokenAccount>,
+    /// CHECK: created by initialize
+    #[account(mut)]
+    pub config: UncheckedAccount<'info>,
+    /// CHECK: created by initialize
+    #[account(mut)]
+    pub mint_lp: UncheckedAccount<'info>,
+    /// CHECK: created by initialize
+    #[account(mut)]
+    pub vault_x: UncheckedAccount<'info>,
+    /// CHECK: created by initialize
+    #[account(mut)]
+    pub vault_y: UncheckedAccount<'info>,
+    /// CHECK: created by initialize
+    #[account(mut)]
+    pub observations: UncheckedAccount<'info>,
+    /// CHECK: created by initialize
+    #[account(mut)]
+    pub oracle: UncheckedAccount<'info>,
+    /// CHECK: created by deposit, the payer's LP token account
+    #[account(mut)]
+    pub payer_ata_lp: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub amm_program: Program<'info, Amm>,
+}
+
+impl<'info> SeedTestPool<'info> {
+    pub fn seed(&mut self, seed: u64, fee: u16, reserve_x: u64, reserve_y: u64) -> Result<()> {
+        require!(reserve_x > 0 && reserve_y > 0, AmmError::InvalidAmount);
+
+        self.mint_test_tokens(true, reserve_x)?;
+        self.mint_test_tokens(false, reserve_y)?;
+        self.initialize_pool(seed, fee)?;
+
+        // The first deposit takes the full amounts and sets the price
+        self.deposit_reserves(reserve_x, reserve_y)
+    }
+
+    fn mint_test_tokens(&mut self, is_x: bool, amount: u64) -> Result<()> {
+        let cpi_program = self.token_program.to_account_info();
+
+        let cpi_accounts = match is_x {
+            true => MintTo {
+                mint: self.mint_x.to_account_info(),
+                to: self.payer_ata_x.to_account_info(),
+                authority: self.payer.to_account_info(),
+            },
+            false => MintTo {
+                mint: self.mint_y.to_account_info(),
+                to: self.payer_ata_y.to_account_info(),
+                authority: self.payer.to_account_info(),
+            },
+        };
+
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+
+        mint_to(cpi_ctx, amount)
+    }
+
+    fn initialize_pool(&mut self, seed: u64, fee: u16) -> Result<()> {
+        let cpi_program = self.amm_program.to_account_info();
+
+        let cpi_accounts = crate::cpi::accounts::Initialize {
+            initializer: self.payer.to_account_info(),
+            global_config: self.global_config.to_account_info(),
+            protocol_stats: self.protocol_stats.to_account_info(),
+            mint_x: self.mint_x.to_account_info(),
+            mint_y: self.mint_y.to_account_info(),
+            mint_lp: self.mint_lp.to_account_info(),
+            vault_x: self.vault_x.to_account_info(),
+            vault_y: self.vault_y.to_account_info(),
+            config: self.config.to_account_info(),
+            observations: self.observations.to_account_info(),
+            oracle: self.oracle.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+            token_program_x: self.token_program.to_account_info(),
+            token_program_y: self.token_program.to_account_info(),
+            system_program: self.system_program.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+
+        crate::cpi::initialize(cpi_ctx, seed, fee, false, false, false, false, 0, None)
+    }
+
+    fn deposit_reserves(&mut self, reserve_x: u64, reserve_y: u64) -> Result<()> {
+        let cpi_program = self.amm_program.to_account_info();
+
+        let cpi_accounts = crate::cpi::accounts::Deposit {
+            lp_provider: self.payer.to_account_info(),
+            owner: self.payer.to_account_info(),
+            mint_x: self.mint_x.to_account_info(),
+            mint_y: self.mint_y.to_account_info(),
+            config: self.config.to_account_info(),
+            lp_mint: self.mint_lp.to_account_info(),
+            vault_x: self.vault_x.to_account_info(),
+            vault_y: self.vault_y.to_account_info(),
+            lp_provider_ata_x: self.payer_ata_x.to_account_info(),
+            lp_provider_ata_y: self.payer_ata_y.to_account_info(),
+            lp_provider_ata_lp: self.payer_ata_lp.to_account_info(),
+            launch_deposit: None,
+            lp_holders: None,
+            token_program: self.token_program.to_account_info(),
+            token_program_x: self.token_program.to_account_info(),
+            token_program_y: self.token_program.to_account_info(),
+            system_program: self.system_program.to_account_info(),
+            associated_token_program: self.associated_token_program.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+
+        crate::cpi::deposit(cpi_ctx, reserve_x, reserve_y, 1)
+    }
+}
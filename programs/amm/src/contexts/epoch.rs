@@ -0,0 +1,339 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::Token;
+use anchor_spl::token_interface::{transfer_checked, TransferChecked, Mint, TokenAccount, TokenInterface};
+
+use crate::state::{Config, EpochSnapshot, StakePosition};
+use crate::errors::AmmError;
+
+#[derive(Accounts)]
+pub struct StakeLp<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub mint_x: InterfaceAccount<'info, Mint>,
+    pub mint_y: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [
+            b"config",
+            mint_x.key().to_bytes().as_ref(),
+            mint_y.key().to_bytes().as_ref(),
+            config.seed.to_le_bytes().as_ref()
+        ],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        seeds = [b"lp", config.key().as_ref()],
+        bump = config.lp_bump,
+    )]
+    pub mint_lp: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        associated_token::mint = mint_lp,
+        associated_token::authority = owner,
+    )]
+    pub owner_ata_lp: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = mint_lp,
+        associated_token::authority = config,
+    )]
+    pub stake_vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = StakePosition::LEN,
+        seeds = [b"stake", config.key().as_ref(), owner.key().as_ref()],
+        bump
+    )]
+    pub position: Account<'info, StakePosition>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> StakeLp<'info> {
+    pub fn stake(&mut self, amount: u64, bumps: &StakeLpBumps) -> Result<()> {
+        require!(amount > 0, AmmError::InvalidAmount);
+        require!(self.config.epoch_duration > 0, AmmError::EpochsDisabled);
+        // Soulbound LP accounts are frozen and can't move into the stake vault
+        require!(!self.config.soulbound, AmmError::InvalidConfig);
+        // The LP mint's transfer hook would call back into this program, which can't be reentered
+        require!(!self.config.track_lp_holders, AmmError::LpHoldersTracked);
+
+        if self.position.config == Pubkey::default() {
+            self.position.set_inner(StakePosition {
+                config: self.config.key(),
+                owner: self.owner.key(),
+                amount: 0,
+                active_epoch: 0,
+                next_claim_epoch: 0,
+                bump: bumps.position,
+            });
+        }
+
+        let new_amount = self.position.amount.checked_add(amount).ok_or(AmmError::InvalidAmount)?;
+        self.restake(new_amount)?;
+        self.transfer_lp(true, amount)?;
+
+        Ok(())
+    }
+
+    pub fn unstake(&mut self, amount: u64) -> Result<()> {
+        require!(amount > 0, AmmError::InvalidAmount);
+
+        let new_amount = self.position.amount.checked_sub(amount).ok_or(AmmError::InsufficientBalance)?;
+        self.restake(new_amount)?;
+        self.transfer_lp(false, amount)?;
+
+        Ok(())
+    }
+
+    // Any change restarts the position from the next epoch, so the amount stays constant over every
+    // epoch it is paid for. Fees of the running epoch are forfeited to the other stakers.
+    fn restake(&mut self, new_amount: u64) -> Result<()> {
+        let config: &mut Config = &mut self.config;
+        let position: &mut StakePosition = &mut self.position;
+
+        let first_unclaimed = position.active_epoch.max(position.next_claim_epoch);
+        require!(position.amount == 0 || first_unclaimed >= config.epoch, AmmError::UnclaimedEpochFees);
+
+        match position.active_epoch <= config.epoch {
+            true => config.staked_active -= position.amount,
+            false => config.staked_pending -= position.amount,
+        }
+        config.staked_pending = config.staked_pending.checked_add(new_amount).ok_or(AmmError::InvalidAmount)?;
+
+        position.amount = new_amount;
+        position.active_epoch = config.epoch + 1;
+        position.next_claim_epoch = config.epoch + 1;
+
+        Ok(())
+    }
+
+    fn transfer_lp(&mut self, to_vault: bool, amount: u64) -> Result<()> {
+        let cpi_program = self.token_program.to_account_info();
+
+        if to_vault {
+            let cpi_accounts = TransferChecked {
+                from: self.owner_ata_lp.to_account_info(),
+                mint: self.mint_lp.to_account_info(),
+                to: self.stake_vault.to_account_info(),
+                authority: self.owner.to_account_info(),
+            };
+
+            let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+
+            return transfer_checked(cpi_ctx, amount, self.mint_lp.decimals);
+        }
+
+        let cpi_accounts = TransferChecked {
+            from: self.stake_vault.to_account_info(),
+            mint: self.mint_lp.to_account_info(),
+            to: self.owner_ata_lp.to_account_info(),
+            authority: self.config.to_account_info(),
+        };
+
+        let mint_x = self.mint_x.key().to_bytes();
+        let mint_y = self.mint_y.key().to_bytes();
+        let seed = self.config.seed.to_le_bytes();
+
+        let seeds = [
+            b"config",
+            mint_x.as_ref(),
+            mint_y.as_ref(),
+            seed.as_ref(),
+            &[self.config.config_bump]
+        ];
+
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+        transfer_checked(cpi_ctx, amount, self.mint_lp.decimals)
+    }
+}
+
+#[derive(Accounts)]
+pub struct AdvanceEpoch<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub mint_x: InterfaceAccount<'info, Mint>,
+    pub mint_y: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [
+            b"config",
+            mint_x.key().to_bytes().as_ref(),
+            mint_y.key().to_bytes().as_ref(),
+            config.seed.to_le_bytes().as_ref()
+        ],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        init,
+        payer = payer,
+        space = EpochSnapshot::LEN,
+        seeds = [b"epoch", config.key().as_ref(), config.epoch.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub snapshot: Account<'info, EpochSnapshot>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> AdvanceEpoch<'info> {
+    // Permissionless: closes the running epoch into a snapshot and activates the pending stake
+    pub fn advance(&mut self, bumps: &AdvanceEpochBumps) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let config_key = self.config.key();
+        let config: &mut Config = &mut self.config;
+
+        require!(config.epoch_duration > 0, AmmError::EpochsDisabled);
+        require!(now >= config.epoch_start_ts.saturating_add(config.epoch_duration), AmmError::EpochNotEnded);
+
+        // Without stakers the fees roll over into the next epoch
+        let fees_y = match config.staked_active {
+            0 => 0,
+            _ => config.epoch_fees_y,
+        };
+
+        self.snapshot.set_inner(EpochSnapshot {
+            config: config_key,
+            epoch: config.epoch,
+            fees_y,
+            staked_lp: config.staked_active,
+            claimed_y: 0,
+            bump: bumps.snapshot,
+            start_ts: config.epoch_start_ts,
+            end_ts: now,
+        });
+
+        config.epoch_fees_y -= fees_y;
+        config.unclaimed_epoch_fees_y = config.unclaimed_epoch_fees_y.checked_add(fees_y).ok_or(AmmError::InvalidAmount)?;
+        config.staked_active = config.staked_active.checked_add(config.staked_pending).ok_or(AmmError::InvalidAmount)?;
+        config.staked_pending = 0;
+        config.epoch += 1;
+        config.epoch_start_ts = now;
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct ClaimEpochFees<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub mint_x: InterfaceAccount<'info, Mint>,
+    #[account(mint::token_program = token_program_y)]
+    pub mint_y: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [
+            b"config",
+            mint_x.key().to_bytes().as_ref(),
+            mint_y.key().to_bytes().as_ref(),
+            config.seed.to_le_bytes().as_ref()
+        ],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        seeds = [b"epoch", config.key().as_ref(), snapshot.epoch.to_le_bytes().as_ref()],
+        bump = snapshot.bump,
+    )]
+    pub snapshot: Account<'info, EpochSnapshot>,
+    #[account(
+        mut,
+        has_one = owner,
+        seeds = [b"stake", config.key().as_ref(), owner.key().as_ref()],
+        bump = position.bump,
+    )]
+    pub position: Account<'info, StakePosition>,
+    #[account(
+        mut,
+        seeds = [b"vault_y", config.key().as_ref()],
+        bump = config.vault_y_bump,
+    )]
+    pub vault_y: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = mint_y,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program_y,
+    )]
+    pub owner_ata_y: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program_y: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> ClaimEpochFees<'info> {
+    // Epochs are claimed in order, skipping past an epoch forfeits it
+    pub fn claim(&mut self) -> Result<()> {
+        let epoch = self.snapshot.epoch;
+
+        require!(
+            epoch >= self.position.active_epoch && epoch >= self.position.next_claim_epoch,
+            AmmError::NotEligible
+        );
+
+        let amount = (self.snapshot.fees_y as u128 * self.position.amount as u128
+            / self.snapshot.staked_lp.max(1) as u128) as u64;
+
+        self.snapshot.claimed_y = self.snapshot.claimed_y.checked_add(amount).ok_or(AmmError::InvalidAmount)?;
+        self.config.unclaimed_epoch_fees_y = self.config.unclaimed_epoch_fees_y
+            .checked_sub(amount)
+            .ok_or(AmmError::InsufficientBalance)?;
+        self.position.next_claim_epoch = epoch + 1;
+
+        if amount > 0 {
+            self.transfer_fees(amount)?;
+        }
+
+        Ok(())
+    }
+
+    fn transfer_fees(&mut self, amount: u64) -> Result<()> {
+        let cpi_program = self.token_program_y.to_account_info();
+
+        let cpi_accounts = TransferChecked {
+            from: self.vault_y.to_account_info(),
+            mint: self.mint_y.to_account_info(),
+            to: self.owner_ata_y.to_account_info(),
+            authority: self.config.to_account_info(),
+        };
+
+        let mint_x = self.mint_x.key().to_bytes();
+        let mint_y = self.mint_y.key().to_bytes();
+        let seed = self.config.seed.to_le_bytes();
+
+        let seeds = [
+            b"config",
+            mint_x.as_ref(),
+            mint_y.as_ref(),
+            seed.as_ref(),
+            &[self.config.config_bump]
+        ];
+
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+        transfer_checked(cpi_ctx, amount, self.mint_y.decimals)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,368 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::token::Token;
+use anchor_spl::token_interface::{close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TokenInterface, TransferChecked};
+
+use crate::state::{Config, EpochSnapshot, GlobalConfig, LaunchDeposit, Observations, PriceOracle, ProtocolStats, StakePosition, WithdrawRequest};
+use crate::errors::AmmError;
+use crate::events::PoolGarbageCollected;
+use crate::constants::{GC_CALLER_SHARE_BPS, GC_INACTIVITY_SECS};
+
+#[derive(Accounts)]
+pub struct GcPool<'info> {
+    #[account(mut)]
+    pub caller: Signer<'info>,
+    #[account(
+        seeds = [b"global"],
+        bump = global_config.bump,
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    /// CHECK: the protocol authority, receives the treasury's share of the rent
+    #[account(mut, address = global_config.authority @ AmmError::Unauthorized)]
+    pub treasury: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"stats"],
+        bump = protocol_stats.bump,
+    )]
+    pub protocol_stats: Account<'info, ProtocolStats>,
+    pub mint_x: InterfaceAccount<'info, Mint>,
+    pub mint_y: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [
+            b"config",
+            mint_x.key().to_bytes().as_ref(),
+            mint_y.key().to_bytes().as_ref(),
+            config.seed.to_le_bytes().as_ref()
+        ],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        seeds = [b"observations", config.key().as_ref()],
+        bump = config.observations_bump,
+    )]
+    pub observations: AccountLoader<'info, Observations>,
+    #[account(
+        mut,
+        seeds = [b"oracle", config.key().as_ref()],
+        bump = config.oracle_bump,
+    )]
+    pub oracle: AccountLoader<'info, PriceOracle>,
+    #[account(
+        seeds = [b"lp", config.key().as_ref()],
+        bump = config.lp_bump,
+    )]
+    pub mint_lp: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        seeds = [b"vault_x", config.key().as_ref()],
+        bump = config.vault_x_bump,
+        token::token_program = token_program_x,
+    )]
+    pub vault_x: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [b"vault_y", config.key().as_ref()],
+        bump = config.vault_y_bump,
+        token::token_program = token_program_y,
+    )]
+    pub vault_y: InterfaceAccount<'info, TokenAccount>,
+    // Receive what the locked minimum liquidity still owns
+    #[account(
+        mut,
+        token::mint = mint_x,
+        token::authority = treasury,
+        token::token_program = token_program_x,
+    )]
+    pub treasury_x: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = mint_y,
+        token::authority = treasury,
+        token::token_program = token_program_y,
+    )]
+    pub treasury_y: InterfaceAccount<'info, TokenAccount>,
+    // Unspent crank rewards go with the rent
+    #[account(
+        mut,
+        seeds = [b"crank", config.key().as_ref()],
+        bump,
+    )]
+    pub crank_vault: SystemAccount<'info>,
+    /// CHECK: the LP holder registry, closed if initialize_lp_holders created it
+    #[account(
+        mut,
+        seeds = [b"lp_holders", mint_lp.key().as_ref()],
+        bump,
+    )]
+    pub lp_holders: UncheckedAccount<'info>,
+    /// CHECK: the LP mint's extra account list, created and closed along with the registry
+    #[account(
+        mut,
+        seeds = [b"extra-account-metas", mint_lp.key().as_ref()],
+        bump,
+    )]
+    pub extra_account_metas: UncheckedAccount<'info>,
+    /// CHECK: the config's LP token account holding stakes, closed if staking created it
+    #[account(
+        mut,
+        address = get_associated_token_address(&config.key(), &mint_lp.key()),
+    )]
+    pub stake_vault: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub token_program_x: Interface<'info, TokenInterface>,
+    pub token_program_y: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> GcPool<'info> {
+    // Closes an abandoned pool's accounts. Everything goes to the caller first, who then pays the
+    // treasury its share. The LP mint can't be closed under the legacy token program and stays, so
+    // the pool's seed can't be reused. Per-user accounts are left to close_orphaned.
+    pub fn gc(&mut self, bumps: &GcPoolBumps) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+
+        // Once every LP left, the vaults only hold what the locked MINIMUM_LIQUIDITY owns. Balances
+        // booked to anyone else have to be claimed first.
+        let (idle_x, idle_y) = self.config.idle_reserves(self.vault_x.amount, self.vault_y.amount)?;
+        require!(
+            self.mint_lp.supply == 0
+                && idle_x as u128 + self.config.lp_fees_x as u128 == self.vault_x.amount as u128
+                && idle_y as u128 + self.config.lp_fees_y as u128 == self.vault_y.amount as u128,
+            AmmError::PoolNotEmpty
+        );
+        // Incentives keep their own reward vaults, which would be orphaned
+        require!(self.config.incentive_count == 0, AmmError::PoolNotEmpty);
+        require!(self.config.deployed_x == 0 && self.config.deployed_y == 0, AmmError::StrategyHoldsReserves);
+        require!(
+            now.saturating_sub(self.config.last_observation_ts) >= GC_INACTIVITY_SECS,
+            AmmError::PoolStillActive
+        );
+
+        let reclaimed = [
+            self.config.to_account_info(),
+            self.observations.to_account_info(),
+            self.oracle.to_account_info(),
+            self.vault_x.to_account_info(),
+            self.vault_y.to_account_info(),
+            self.crank_vault.to_account_info(),
+            self.lp_holders.to_account_info(),
+            self.extra_account_metas.to_account_info(),
+            self.stake_vault.to_account_info(),
+        ]
+        .iter()
+        .map(|info| info.lamports())
+        .sum::<u64>();
+        let caller_reward = (reclaimed as u128 * GC_CALLER_SHARE_BPS as u128 / 10_000) as u64;
+        let treasury_share = reclaimed - caller_reward;
+
+        self.sweep_vault(true)?;
+        self.sweep_vault(false)?;
+        self.close_token_account(self.token_program_x.to_account_info(), self.vault_x.to_account_info())?;
+        self.close_token_account(self.token_program_y.to_account_info(), self.vault_y.to_account_info())?;
+        // Without LP supply there are no stakes left, the vault is empty
+        if self.stake_vault.owner == &Token::id() {
+            self.close_token_account(self.token_program.to_account_info(), self.stake_vault.to_account_info())?;
+        }
+        if self.lp_holders.owner == &crate::ID {
+            close_program_account(&self.lp_holders, &self.caller)?;
+            close_program_account(&self.extra_account_metas, &self.caller)?;
+        }
+        self.drain_crank_vault(bumps.crank_vault)?;
+        self.observations.close(self.caller.to_account_info())?;
+        self.oracle.close(self.caller.to_account_info())?;
+        self.config.close(self.caller.to_account_info())?;
+
+        self.pay_treasury(treasury_share)?;
+
+        self.protocol_stats.total_pools = self.protocol_stats.total_pools.saturating_sub(1);
+
+        emit!(PoolGarbageCollected {
+            config: self.config.key(),
+            caller: self.caller.key(),
+            caller_reward,
+            treasury_share,
+        });
+
+        Ok(())
+    }
+
+    fn sweep_vault(&mut self, is_x: bool) -> Result<()> {
+        let (cpi_program, vault, mint, treasury) = match is_x {
+            true => (self.token_program_x.to_account_info(), &self.vault_x, &self.mint_x, &self.treasury_x),
+            false => (self.token_program_y.to_account_info(), &self.vault_y, &self.mint_y, &self.treasury_y),
+        };
+        if vault.amount == 0 {
+            return Ok(());
+        }
+
+        let cpi_accounts = TransferChecked {
+            from: vault.to_account_info(),
+            mint: mint.to_account_info(),
+            to: treasury.to_account_info(),
+            authority: self.config.to_account_info(),
+        };
+
+        let mint_x = self.mint_x.key().to_bytes();
+        let mint_y = self.mint_y.key().to_bytes();
+        let seed = self.config.seed.to_le_bytes();
+
+        let seeds = [
+            b"config",
+            mint_x.as_ref(),
+            mint_y.as_ref(),
+            seed.as_ref(),
+            &[self.config.config_bump]
+        ];
+
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+        transfer_checked(cpi_ctx, vault.amount, mint.decimals)?;
+
+        Ok(())
+    }
+
+    fn close_token_account(&self, cpi_program: AccountInfo<'info>, account: AccountInfo<'info>) -> Result<()> {
+        let cpi_accounts = CloseAccount {
+            account,
+            destination: self.caller.to_account_info(),
+            authority: self.config.to_account_info(),
+        };
+
+        let mint_x = self.mint_x.key().to_bytes();
+        let mint_y = self.mint_y.key().to_bytes();
+        let seed = self.config.seed.to_le_bytes();
+
+        let seeds = [
+            b"config",
+            mint_x.as_ref(),
+            mint_y.as_ref(),
+            seed.as_ref(),
+            &[self.config.config_bump]
+        ];
+
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+        close_account(cpi_ctx)?;
+
+        Ok(())
+    }
+
+    fn drain_crank_vault(&mut self, crank_bump: u8) -> Result<()> {
+        let amount = self.crank_vault.lamports();
+        if amount == 0 {
+            return Ok(());
+        }
+
+        let cpi_program = self.system_program.to_account_info();
+
+        let cpi_accounts = Transfer {
+            from: self.crank_vault.to_account_info(),
+            to: self.caller.to_account_info(),
+        };
+
+        let config = self.config.key();
+        let seeds = [b"crank", config.as_ref(), &[crank_bump]];
+
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+        transfer(cpi_ctx, amount)?;
+
+        Ok(())
+    }
+
+    fn pay_treasury(&mut self, amount: u64) -> Result<()> {
+        let cpi_program = self.system_program.to_account_info();
+
+        let cpi_accounts = Transfer {
+            from: self.caller.to_account_info(),
+            to: self.treasury.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+
+        transfer(cpi_ctx, amount)?;
+
+        Ok(())
+    }
+}
+
+// Hands a program owned account's rent to `destination` and gives it back to the system program,
+// the same as Anchor's close constraint
+fn close_program_account<'info>(info: &AccountInfo<'info>, destination: &AccountInfo<'info>) -> Result<()> {
+    destination.add_lamports(info.lamports())?;
+    info.sub_lamports(info.lamports())?;
+    info.assign(&System::id());
+    info.resize(0)?;
+
+    Ok(())
+}
+
+// Per-user accounts of a garbage collected pool. Their number isn't bounded, so gc_pool can't close
+// them itself.
+#[derive(Accounts)]
+pub struct CloseOrphaned<'info> {
+    #[account(mut)]
+    pub caller: Signer<'info>,
+    /// CHECK: address of the collected pool, only accepted once gc_pool closed it
+    #[account(constraint = config.owner != &crate::ID @ AmmError::PoolStillActive)]
+    pub config: UncheckedAccount<'info>,
+}
+
+impl<'info> CloseOrphaned<'info> {
+    // Permissionless: closes LaunchDeposit, WithdrawRequest, StakePosition and EpochSnapshot accounts
+    // of the pool. The first three are each followed by their owner, who gets the rent back, snapshot
+    // rent goes to the caller like gc_pool's. The pool had no incentives, so there are no claims.
+    pub fn close_orphaned(&mut self, accounts: &[AccountInfo<'info>]) -> Result<()> {
+        let mut accounts = accounts.iter();
+
+        while let Some(info) = accounts.next() {
+            require_keys_eq!(*info.owner, crate::ID, AmmError::InvalidConfig);
+            let discriminator = info.try_borrow_data()?.get(..8).map(<[u8]>::to_vec).ok_or(AmmError::InvalidConfig)?;
+
+            let (config, owner) = match discriminator.as_slice() {
+                d if d == LaunchDeposit::DISCRIMINATOR => {
+                    let account = Account::<LaunchDeposit>::try_from(info)?;
+                    (account.config, Some(account.owner))
+                },
+                d if d == WithdrawRequest::DISCRIMINATOR => {
+                    let account = Account::<WithdrawRequest>::try_from(info)?;
+                    (account.config, Some(account.owner))
+                },
+                d if d == StakePosition::DISCRIMINATOR => {
+                    let account = Account::<StakePosition>::try_from(info)?;
+                    (account.config, Some(account.owner))
+                },
+                d if d == EpochSnapshot::DISCRIMINATOR => (Account::<EpochSnapshot>::try_from(info)?.config, None),
+                _ => return err!(AmmError::InvalidConfig),
+            };
+            require_keys_eq!(config, self.config.key(), AmmError::InvalidConfig);
+
+            let destination = match owner {
+                Some(owner) => {
+                    let destination = accounts.next().ok_or(AmmError::InvalidConfig)?;
+                    require_keys_eq!(destination.key(), owner, AmmError::InvalidConfig);
+                    destination
+                },
+                None => self.caller.as_ref(),
+            };
+
+            close_program_account(info, destination)?;
+        }
+
+        Ok(())
+    }
+}
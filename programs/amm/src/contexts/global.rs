@@ -0,0 +1,127 @@
+use anchor_lang::prelude::*;
+
+use crate::program::Amm;
+use crate::state::{GlobalConfig, ProtocolStats, MAX_ALLOWED_QUOTE_MINTS};
+use crate::errors::AmmError;
+
+#[derive(Accounts)]
+pub struct InitializeGlobal<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        init,
+        payer = admin,
+        space = GlobalConfig::LEN,
+        seeds = [b"global"],
+        bump
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    #[account(
+        init,
+        payer = admin,
+        space = ProtocolStats::LEN,
+        seeds = [b"stats"],
+        bump
+    )]
+    pub protocol_stats: Account<'info, ProtocolStats>,
+    // Only the upgrade authority of the program can set up the protocol level config
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()) @ AmmError::InvalidConfig)]
+    pub program: Program<'info, Amm>,
+    #[account(constraint = program_data.upgrade_authority_address == Some(admin.key()) @ AmmError::Unauthorized)]
+    pub program_data: Account<'info, ProgramData>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitializeGlobal<'info> {
+    pub fn init(&mut self, authority: Pubkey, protocol_fee: u16, bumps: &InitializeGlobalBumps) -> Result<()> {
+        require!(protocol_fee <= 10_000, AmmError::InvalidFee);
+
+        self.global_config.set_inner(GlobalConfig {
+            authority,
+            protocol_fee,
+            bump: bumps.global_config,
+            curated: false,
+            allowed_quote_mints: Vec::new(),
+            max_platform_fee: 0,
+            insurance_fee: 0,
+            fee_target_mint: Pubkey::default(),
+            reserved: [0; 32],
+        });
+
+        self.protocol_stats.set_inner(ProtocolStats {
+            total_pools: 0,
+            total_swaps: 0,
+            mints: Vec::new(),
+            bump: bumps.protocol_stats,
+            reserved: [0; 64],
+        });
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct UpdateGlobal<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        has_one = authority @ AmmError::Unauthorized,
+        seeds = [b"global"],
+        bump = global_config.bump,
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+}
+
+impl<'info> UpdateGlobal<'info> {
+    pub fn set_curated(&mut self, curated: bool) -> Result<()> {
+        self.global_config.curated = curated;
+
+        Ok(())
+    }
+
+    pub fn set_max_platform_fee(&mut self, max_platform_fee: u16) -> Result<()> {
+        require!(max_platform_fee <= 10_000, AmmError::InvalidFee);
+
+        self.global_config.max_platform_fee = max_platform_fee;
+
+        Ok(())
+    }
+
+    pub fn set_insurance_fee(&mut self, insurance_fee: u16) -> Result<()> {
+        require!(
+            self.global_config.protocol_fee as u32 + insurance_fee as u32 <= 10_000,
+            AmmError::InvalidFee
+        );
+
+        self.global_config.insurance_fee = insurance_fee;
+
+        Ok(())
+    }
+
+    // Enables harvest_fees into `mint`, or disables it with the default pubkey
+    pub fn set_fee_target_mint(&mut self, mint: Pubkey) -> Result<()> {
+        self.global_config.fee_target_mint = mint;
+
+        Ok(())
+    }
+
+    pub fn add_allowed_quote_mint(&mut self, mint: Pubkey) -> Result<()> {
+        let allowed = &mut self.global_config.allowed_quote_mints;
+
+        require!(!allowed.contains(&mint), AmmError::InvalidConfig);
+        require!(allowed.len() < MAX_ALLOWED_QUOTE_MINTS, AmmError::AllowlistFull);
+
+        allowed.push(mint);
+
+        Ok(())
+    }
+
+    pub fn remove_allowed_quote_mint(&mut self, mint: Pubkey) -> Result<()> {
+        let allowed = &mut self.global_config.allowed_quote_mints;
+        let index = allowed.iter().position(|m| *m == mint).ok_or(AmmError::InvalidConfig)?;
+
+        allowed.remove(index);
+
+        Ok(())
+    }
+}
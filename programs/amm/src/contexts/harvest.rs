@@ -0,0 +1,221 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{transfer_checked, TransferChecked, Mint, TokenAccount, TokenInterface};
+
+use crate::state::{Config, GlobalConfig, Observations, PriceOracle};
+use crate::errors::AmmError;
+use crate::events::ProtocolFeesHarvested;
+use crate::constants::{HARVEST_MAX_SLIPPAGE_BPS, ORACLE_TWAP_WINDOW_SECS};
+use crate::math::mul_div_wide;
+
+#[derive(Accounts)]
+pub struct HarvestFees<'info> {
+    pub caller: Signer<'info>,
+    #[account(
+        seeds = [b"global"],
+        bump = global_config.bump,
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    #[account(mint::token_program = token_program_x)]
+    pub mint_x: InterfaceAccount<'info, Mint>,
+    #[account(mint::token_program = token_program_y)]
+    pub mint_y: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [
+            b"config",
+            mint_x.key().to_bytes().as_ref(),
+            mint_y.key().to_bytes().as_ref(),
+            config.seed.to_le_bytes().as_ref()
+        ],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        seeds = [b"observations", config.key().as_ref()],
+        bump = config.observations_bump,
+    )]
+    pub observations: AccountLoader<'info, Observations>,
+    #[account(
+        mut,
+        seeds = [b"oracle", config.key().as_ref()],
+        bump = config.oracle_bump,
+    )]
+    pub oracle: AccountLoader<'info, PriceOracle>,
+    #[account(
+        mut,
+        seeds = [b"vault_x", config.key().as_ref()],
+        bump = config.vault_x_bump,
+    )]
+    pub vault_x: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [b"vault_y", config.key().as_ref()],
+        bump = config.vault_y_bump,
+    )]
+    pub vault_y: InterfaceAccount<'info, TokenAccount>,
+    // Any token account of the target mint owned by the protocol authority
+    #[account(
+        mut,
+        token::mint = global_config.fee_target_mint,
+        token::authority = global_config.authority,
+    )]
+    pub treasury: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program_x: Interface<'info, TokenInterface>,
+    pub token_program_y: Interface<'info, TokenInterface>,
+}
+
+impl<'info> HarvestFees<'info> {
+    // Sells the protocol fees accrued in the pool's other token into the target mint through the
+    // pool's own curve and sends every protocol fee in the target mint to the treasury
+    pub fn harvest(&mut self) -> Result<()> {
+        let target = self.global_config.fee_target_mint;
+        require!(
+            target == self.mint_x.key() || target == self.mint_y.key(),
+            AmmError::MintNotAllowed
+        );
+        let now = Clock::get()?.unix_timestamp;
+        require!(!self.config.is_locked(now), AmmError::AMMLocked);
+
+        // Selling X when the target is Y and the other way round
+        let is_x = target == self.mint_y.key();
+        let (amount_in, fees_out) = match is_x {
+            true => (self.config.protocol_fees_x, self.config.protocol_fees_y),
+            false => (self.config.protocol_fees_y, self.config.protocol_fees_x),
+        };
+        require!(amount_in > 0 || fees_out > 0, AmmError::InvalidAmount);
+
+        let mut amount_out = 0;
+        if amount_in > 0 {
+            amount_out = self.sell_fees(is_x, amount_in, now)?;
+        }
+
+        let amount_sent = fees_out.checked_add(amount_out).ok_or(AmmError::InvalidAmount)?;
+        match is_x {
+            true => self.config.protocol_fees_y = 0,
+            false => self.config.protocol_fees_x = 0,
+        }
+        self.transfer_to_treasury(!is_x, amount_sent)?;
+        self.publish_oracle()?;
+
+        emit!(ProtocolFeesHarvested {
+            config: self.config.key(),
+            caller: self.caller.key(),
+            is_x,
+            amount_in,
+            amount_out,
+            amount_sent,
+        });
+
+        Ok(())
+    }
+
+    // Moves `amount_in` of protocol fees into the reserves as a swap at the pool fee, bounded by
+    // the oracle TWAP. The tokens never leave the vault, only the booking changes.
+    fn sell_fees(&mut self, is_x: bool, amount_in: u64, now: i64) -> Result<u64> {
+        require!(self.config.is_open(now), AmmError::PoolNotOpen);
+        let (reserve_x, reserve_y) = self.config.reserves(self.vault_x.amount, self.vault_y.amount)?;
+        require!(reserve_x > 0 && reserve_y > 0, AmmError::InsufficientBalance);
+
+        // Bring the oracle up to now so its TWAP covers the window right before this trade
+        self.config.accumulate_prices(reserve_x, reserve_y, now);
+        self.publish_oracle()?;
+
+        let twap_price = {
+            let oracle = self.oracle.load()?;
+            require!(oracle.twap_window >= ORACLE_TWAP_WINDOW_SECS, AmmError::OracleUnavailable);
+            match is_x {
+                true => oracle.twap_price_x,
+                false => oracle.twap_price_y,
+            }
+        };
+
+        let fee = self.config.swap_fee(is_x, Clock::get()?.slot);
+        let quote = self.config
+            .swap_quote(is_x, reserve_x, reserve_y, amount_in, fee, now)
+            .ok_or(AmmError::InvalidAmount)?;
+
+        // The pool fee is an expected cost, anything beyond it and HARVEST_MAX_SLIPPAGE_BPS is
+        // price impact or a manipulated spot price
+        let twap_out = mul_div_wide(amount_in, twap_price, 1 << 64, false).ok_or(AmmError::InvalidAmount)?;
+        let kept = 10_000u16.saturating_sub(fee).saturating_sub(HARVEST_MAX_SLIPPAGE_BPS);
+        let min_out = mul_div_wide(twap_out, kept as u128, 10_000, false).ok_or(AmmError::InvalidAmount)?;
+        require!(quote.amount_out >= min_out, AmmError::SlippageExceeded);
+        require_neq!(quote.amount_out, 0, AmmError::InvalidAmount);
+        let (out_x, out_y) = match is_x {
+            true => (0, quote.amount_out),
+            false => (quote.amount_out, 0),
+        };
+        self.config.check_idle(self.vault_x.amount, self.vault_y.amount, out_x, out_y)?;
+
+        let config: &mut Config = &mut self.config;
+        match is_x {
+            true => config.protocol_fees_x = 0,
+            false => config.protocol_fees_y = 0,
+        }
+        config
+            .credit_lp_fee(is_x != config.fee_on_output, quote.fee)
+            .ok_or(AmmError::InvalidAmount)?;
+
+        Ok(quote.amount_out)
+    }
+
+    // Publishes the spot price of the current reserves with the observation it's based on
+    fn publish_oracle(&mut self) -> Result<()> {
+        self.vault_x.reload()?;
+        self.vault_y.reload()?;
+
+        let (reserve_x, reserve_y) = self.config.reserves(self.vault_x.amount, self.vault_y.amount)?;
+        let mut observations = self.observations.load_mut()?;
+        observations.write(
+            self.config.last_observation_ts,
+            self.config.price_x_cumulative,
+            self.config.price_y_cumulative,
+        );
+        let mut oracle = self.oracle.load_mut()?;
+        oracle.update(&self.config, &observations, reserve_x, reserve_y, Clock::get()?.slot);
+
+        Ok(())
+    }
+
+    fn transfer_to_treasury(&mut self, is_x: bool, amount: u64) -> Result<()> {
+        let (cpi_program, cpi_accounts, mint_decimals) = match is_x {
+            true => (self.token_program_x.to_account_info(), TransferChecked {
+                from: self.vault_x.to_account_info(),
+                mint: self.mint_x.to_account_info(),
+                to: self.treasury.to_account_info(),
+                authority: self.config.to_account_info(),
+            }, self.mint_x.decimals),
+
+            false => (self.token_program_y.to_account_info(), TransferChecked {
+                from: self.vault_y.to_account_info(),
+                mint: self.mint_y.to_account_info(),
+                to: self.treasury.to_account_info(),
+                authority: self.config.to_account_info(),
+            }, self.mint_y.decimals),
+        };
+
+        let mint_x = self.mint_x.key().to_bytes();
+        let mint_y = self.mint_y.key().to_bytes();
+        let seed = self.config.seed.to_le_bytes();
+
+        let seeds = [
+            b"config",
+            mint_x.as_ref(),
+            mint_y.as_ref(),
+            seed.as_ref(),
+            &[self.config.config_bump]
+        ];
+
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+        transfer_checked(cpi_ctx, amount, mint_decimals)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,320 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_interface::{transfer_checked, TransferChecked, Mint, TokenAccount, TokenInterface};
+
+use crate::state::{Config, EpochSnapshot, Incentive, IncentiveClaim, StakePosition};
+use crate::errors::AmmError;
+
+#[derive(Accounts)]
+pub struct CreateIncentive<'info> {
+    #[account(mut)]
+    pub funder: Signer<'info>,
+    pub mint_x: InterfaceAccount<'info, Mint>,
+    pub mint_y: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [
+            b"config",
+            mint_x.key().to_bytes().as_ref(),
+            mint_y.key().to_bytes().as_ref(),
+            config.seed.to_le_bytes().as_ref()
+        ],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+    #[account(mint::token_program = token_program)]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        init,
+        payer = funder,
+        space = Incentive::LEN,
+        seeds = [b"incentive", config.key().as_ref(), config.incentive_count.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub incentive: Account<'info, Incentive>,
+    #[account(
+        init,
+        payer = funder,
+        associated_token::mint = reward_mint,
+        associated_token::authority = incentive,
+    )]
+    pub reward_vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = reward_mint,
+        associated_token::authority = funder,
+    )]
+    pub funder_ata: InterfaceAccount<'info, TokenAccount>,
+
+    // Program of the reward mint, legacy SPL Token or Token-2022
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> CreateIncentive<'info> {
+    // Permissionless: anyone can pay the pool's stakers to keep their liquidity in
+    pub fn create(&mut self, amount: u64, start_ts: i64, end_ts: i64, bumps: &CreateIncentiveBumps) -> Result<()> {
+        require!(amount > 0, AmmError::InvalidAmount);
+        // Rewards are paid per closed epoch to the LP staked for it
+        require!(self.config.epoch_duration > 0, AmmError::EpochsDisabled);
+        require!(start_ts >= Clock::get()?.unix_timestamp && end_ts > start_ts, AmmError::InvalidSchedule);
+
+        self.incentive.set_inner(Incentive {
+            config: self.config.key(),
+            index: self.config.incentive_count,
+            reward_mint: self.reward_mint.key(),
+            funder: self.funder.key(),
+            amount,
+            start_ts,
+            end_ts,
+            claimed: 0,
+            bump: bumps.incentive,
+            reclaimed: 0,
+            // Epochs closed before the incentive existed emitted none of it
+            next_reclaim_epoch: self.config.epoch,
+        });
+
+        self.config.incentive_count += 1;
+
+        let cpi_accounts = TransferChecked {
+            from: self.funder_ata.to_account_info(),
+            mint: self.reward_mint.to_account_info(),
+            to: self.reward_vault.to_account_info(),
+            authority: self.funder.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(self.token_program.to_account_info(), cpi_accounts);
+
+        transfer_checked(cpi_ctx, amount, self.reward_mint.decimals)
+    }
+}
+
+#[derive(Accounts)]
+pub struct ClaimIncentive<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        has_one = config,
+        has_one = reward_mint,
+        seeds = [b"incentive", config.key().as_ref(), incentive.index.to_le_bytes().as_ref()],
+        bump = incentive.bump,
+    )]
+    pub incentive: Account<'info, Incentive>,
+    #[account(
+        has_one = config,
+        seeds = [b"epoch", config.key().as_ref(), snapshot.epoch.to_le_bytes().as_ref()],
+        bump = snapshot.bump,
+    )]
+    pub snapshot: Account<'info, EpochSnapshot>,
+    #[account(
+        has_one = owner,
+        seeds = [b"stake", config.key().as_ref(), owner.key().as_ref()],
+        bump = position.bump,
+    )]
+    pub position: Account<'info, StakePosition>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = IncentiveClaim::LEN,
+        seeds = [b"incentive_claim", incentive.key().as_ref(), owner.key().as_ref()],
+        bump
+    )]
+    pub claim: Account<'info, IncentiveClaim>,
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        associated_token::mint = reward_mint,
+        associated_token::authority = incentive,
+    )]
+    pub reward_vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = reward_mint,
+        associated_token::authority = owner,
+    )]
+    pub owner_ata: InterfaceAccount<'info, TokenAccount>,
+
+    // Program of the reward mint, legacy SPL Token or Token-2022
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> ClaimIncentive<'info> {
+    // Same rules as epoch fees: claimed per closed epoch, in order, for the stake held over the whole epoch
+    pub fn claim(&mut self, bumps: &ClaimIncentiveBumps) -> Result<()> {
+        require!(!self.incentive.claims_expired(Clock::get()?.unix_timestamp), AmmError::IncentiveExpired);
+
+        if self.claim.incentive == Pubkey::default() {
+            self.claim.set_inner(IncentiveClaim {
+                incentive: self.incentive.key(),
+                owner: self.owner.key(),
+                next_claim_epoch: 0,
+                bump: bumps.claim,
+            });
+        }
+
+        let epoch = self.snapshot.epoch;
+
+        require!(
+            epoch >= self.position.active_epoch && epoch >= self.claim.next_claim_epoch,
+            AmmError::NotEligible
+        );
+
+        let emitted = self.incentive.emitted(self.snapshot.start_ts, self.snapshot.end_ts);
+        let amount = (emitted as u128 * self.position.amount as u128
+            / self.snapshot.staked_lp.max(1) as u128) as u64;
+
+        self.incentive.claimed = self.incentive.claimed.checked_add(amount).ok_or(AmmError::InvalidAmount)?;
+        self.claim.next_claim_epoch = epoch + 1;
+
+        if amount > 0 {
+            self.transfer_rewards(amount)?;
+        }
+
+        Ok(())
+    }
+
+    fn transfer_rewards(&mut self, amount: u64) -> Result<()> {
+        let cpi_program = self.token_program.to_account_info();
+
+        let cpi_accounts = TransferChecked {
+            from: self.reward_vault.to_account_info(),
+            mint: self.reward_mint.to_account_info(),
+            to: self.owner_ata.to_account_info(),
+            authority: self.incentive.to_account_info(),
+        };
+
+        let config = self.config.key();
+        let index = self.incentive.index.to_le_bytes();
+
+        let seeds = [
+            b"incentive",
+            config.as_ref(),
+            index.as_ref(),
+            &[self.incentive.bump]
+        ];
+
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+        transfer_checked(cpi_ctx, amount, self.reward_mint.decimals)?;
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct ReclaimIncentive<'info> {
+    pub funder: Signer<'info>,
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        has_one = config,
+        has_one = funder,
+        has_one = reward_mint,
+        seeds = [b"incentive", config.key().as_ref(), incentive.index.to_le_bytes().as_ref()],
+        bump = incentive.bump,
+    )]
+    pub incentive: Account<'info, Incentive>,
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        associated_token::mint = reward_mint,
+        associated_token::authority = incentive,
+    )]
+    pub reward_vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = reward_mint,
+        token::authority = funder,
+    )]
+    pub funder_account: InterfaceAccount<'info, TokenAccount>,
+
+    // Program of the reward mint, legacy SPL Token or Token-2022
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> ReclaimIncentive<'info> {
+    // Returns rewards nobody is owed once the schedule ended: those emitted during the closed epochs
+    // in `snapshots` that had nothing staked, passed in epoch order from next_reclaim_epoch on, and
+    // after the claim window everything left in the vault, rounding dust included
+    pub fn reclaim(&mut self, snapshots: &[AccountInfo<'info>]) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(now >= self.incentive.end_ts, AmmError::IncentiveActive);
+
+        let expired = self.incentive.claims_expired(now);
+        let amount = match expired {
+            true => self.reward_vault.amount,
+            false => self.unstaked_emissions(snapshots)?,
+        };
+        // A batch of epochs that all had LP staked pays nothing but still moves next_reclaim_epoch on
+        require!(amount > 0 || (!expired && !snapshots.is_empty()), AmmError::InvalidAmount);
+        if amount == 0 {
+            return Ok(());
+        }
+
+        self.incentive.reclaimed = self.incentive.reclaimed.checked_add(amount).ok_or(AmmError::InvalidAmount)?;
+
+        self.transfer_to_funder(amount)
+    }
+
+    fn unstaked_emissions(&mut self, snapshots: &[AccountInfo<'info>]) -> Result<u64> {
+        let config = self.config.key();
+        let mut amount: u64 = 0;
+
+        for info in snapshots {
+            let snapshot = Account::<EpochSnapshot>::try_from(info)?;
+            let expected = Pubkey::create_program_address(
+                &[b"epoch", config.as_ref(), &snapshot.epoch.to_le_bytes(), &[snapshot.bump]],
+                &crate::ID,
+            ).map_err(|_| AmmError::InvalidConfig)?;
+            require_keys_eq!(snapshot.key(), expected, AmmError::InvalidConfig);
+            require!(snapshot.epoch == self.incentive.next_reclaim_epoch, AmmError::InvalidConfig);
+
+            if snapshot.staked_lp == 0 {
+                let emitted = self.incentive.emitted(snapshot.start_ts, snapshot.end_ts);
+                amount = amount.checked_add(emitted).ok_or(AmmError::InvalidAmount)?;
+            }
+            self.incentive.next_reclaim_epoch += 1;
+        }
+
+        Ok(amount)
+    }
+
+    fn transfer_to_funder(&mut self, amount: u64) -> Result<()> {
+        let cpi_program = self.token_program.to_account_info();
+
+        let cpi_accounts = TransferChecked {
+            from: self.reward_vault.to_account_info(),
+            mint: self.reward_mint.to_account_info(),
+            to: self.funder_account.to_account_info(),
+            authority: self.incentive.to_account_info(),
+        };
+
+        let config = self.config.key();
+        let index = self.incentive.index.to_le_bytes();
+
+        let seeds = [
+            b"incentive",
+            config.as_ref(),
+            index.as_ref(),
+            &[self.incentive.bump]
+        ];
+
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+        transfer_checked(cpi_ctx, amount, self.reward_mint.decimals)?;
+
+        Ok(())
+    }
+}
@@ -1,8 +1,18 @@
 use anchor_lang::prelude::*;
-use anchor_spl::{associated_token::AssociatedToken, token::{Mint, Token, TokenAccount}};
+use anchor_lang::system_program::{allocate, assign, create_account, transfer, Allocate, Assign, CreateAccount, Transfer};
+use anchor_spl::token_2022::{initialize_mint2, InitializeMint2};
+use anchor_spl::token_2022::spl_token_2022::extension::{BaseStateWithExtensions, ExtensionType, StateWithExtensions};
+use anchor_spl::token_2022::spl_token_2022::state::Mint as MintState;
+use anchor_spl::token_2022_extensions::{transfer_hook_initialize, TransferHookInitialize};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 
 
-use crate::state::Config;
+use crate::state::{Config, CpiPolicy, GlobalConfig, Observations, PriceOracle, ProtocolStats, PRICE_ORACLE_VERSION};
+use crate::errors::AmmError;
+use crate::constants::{
+    RISK_CLOSE_AUTHORITY_X, RISK_CLOSE_AUTHORITY_Y, RISK_FREEZE_AUTHORITY_X, RISK_FREEZE_AUTHORITY_Y,
+    RISK_PERMANENT_DELEGATE_X, RISK_PERMANENT_DELEGATE_Y,
+};
 
 
 #[derive(Accounts)]
@@ -10,35 +20,52 @@ use crate::state::Config;
 pub struct Initialize<'info> {
     #[account(mut)]
     pub initializer: Signer<'info>,
-    pub mint_x: Account<'info, Mint>,
-    pub mint_y: Account<'info, Mint>,
     #[account(
-        init,
-        payer = initializer,
+        seeds = [b"global"],
+        bump = global_config.bump,
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    #[account(
+        mut,
+        seeds = [b"stats"],
+        bump = protocol_stats.bump,
+    )]
+    pub protocol_stats: Account<'info, ProtocolStats>,
+    #[account(mint::token_program = token_program_x)]
+    pub mint_x: InterfaceAccount<'info, Mint>,
+    #[account(mint::token_program = token_program_y)]
+    pub mint_y: InterfaceAccount<'info, Mint>,
+    /// CHECK: created in init, with a transfer hook extension when the pool tracks LP holders
+    #[account(
+        mut,
         seeds = [b"lp", config.key().as_ref()],
         bump,
-        mint::decimals = 6,
-        mint::authority = config
     )]
-    pub mint_lp: Account<'info, Mint>,
+    pub mint_lp: UncheckedAccount<'info>,
     #[account(
         init,
         payer = initializer,
-        associated_token::mint = mint_x,
-        associated_token::authority = config,
+        seeds = [b"vault_x", config.key().as_ref()],
+        bump,
+        token::mint = mint_x,
+        token::authority = config,
+        token::token_program = token_program_x,
     )]
-    pub vault_x: Account<'info, TokenAccount>,
+    pub vault_x: InterfaceAccount<'info, TokenAccount>,
     #[account(
         init,
         payer = initializer,
-        associated_token::mint = mint_y,
-        associated_token::authority = config,
+        seeds = [b"vault_y", config.key().as_ref()],
+        bump,
+        token::mint = mint_y,
+        token::authority = config,
+        token::token_program = token_program_y,
     )]
-    pub vault_y: Account<'info, TokenAccount>, 
+    pub vault_y: InterfaceAccount<'info, TokenAccount>,
     #[account(
         init,
         payer = initializer,
-        space = Config::INIT_SPACE,
+        space = Config::LEN,
         seeds = [
             b"config", 
             mint_x.key().to_bytes().as_ref(),
@@ -48,25 +75,256 @@ pub struct Initialize<'info> {
         bump
     )]
     pub config: Account<'info, Config>,
+    #[account(
+        init,
+        payer = initializer,
+        space = Observations::LEN,
+        seeds = [b"observations", config.key().as_ref()],
+        bump
+    )]
+    pub observations: AccountLoader<'info, Observations>,
+    #[account(
+        init,
+        payer = initializer,
+        space = PriceOracle::LEN,
+        seeds = [b"oracle", config.key().as_ref()],
+        bump
+    )]
+    pub oracle: AccountLoader<'info, PriceOracle>,
 
-    pub token_program: Program<'info, Token>,
+    // LP mint program, legacy SPL Token or Token-2022 for pools tracking LP holders
+    pub token_program: Interface<'info, TokenInterface>,
+    // Programs of mint_x and mint_y, legacy SPL Token or Token-2022 independently
+    pub token_program_x: Interface<'info, TokenInterface>,
+    pub token_program_y: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
 }
 
 impl<'info> Initialize<'info> {
-    pub fn init(&mut self, seed: u64, fee: u16, authority: Option<Pubkey>, bumps: &InitializeBumps) -> Result<()> {
+    #[allow(clippy::too_many_arguments)]
+    pub fn init(
+        &mut self,
+        seed: u64,
+        fee: u16,
+        fee_on_output: bool,
+        soulbound: bool,
+        compliant: bool,
+        track_lp_holders: bool,
+        open_time: i64,
+        authority: Option<Pubkey>,
+        bumps: &InitializeBumps,
+    ) -> Result<()> {
+        require!(fee <= 10_000, AmmError::InvalidFee);
+        // Soulbound LP accounts are always frozen, a compliance freeze couldn't be told apart
+        require!(!(soulbound && compliant), AmmError::InvalidConfig);
+        require!(!compliant || authority.is_some(), AmmError::InvalidConfig);
+        require!(
+            self.global_config.allows_pair(&self.mint_x.key(), &self.mint_y.key()),
+            AmmError::MintNotAllowed
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let risk_flags = self.mint_risk_flags()?;
+        self.create_lp_mint(track_lp_holders, bumps.mint_lp)?;
+
         self.config.set_inner( Config {
             seed,
             authority,
             mint_x: self.mint_x.key(),
             mint_y: self.mint_y.key(),
             fee,
+            fee_on_output,
             locked: false,
             config_bump: bumps.config,
             lp_bump: bumps.mint_lp,
+            protocol_fees_x: 0,
+            protocol_fees_y: 0,
+            price_x_cumulative: 0,
+            price_y_cumulative: 0,
+            last_observation_ts: now,
+            observations_bump: bumps.observations,
+            soulbound,
+            risk_flags,
+            lp_fees_x: 0,
+            lp_fees_y: 0,
+            last_trade_price: 0,
+            last_trade_is_x: false,
+            last_trade_slot: 0,
+            launch_fee: 0,
+            launch_fee_start_slot: 0,
+            launch_fee_slots: 0,
+            launch_fee_on_x: false,
+            cpi_policy: CpiPolicy::Open,
+            cpi_programs: Vec::new(),
+            epoch_duration: 0,
+            epoch: 0,
+            epoch_start_ts: 0,
+            epoch_fees_y: 0,
+            unclaimed_epoch_fees_y: 0,
+            staked_active: 0,
+            staked_pending: 0,
+            insurance_x: 0,
+            insurance_y: 0,
+            insurance_payout_x: 0,
+            insurance_payout_y: 0,
+            insurance_payout_recipient: Pubkey::default(),
+            insurance_payout_ts: 0,
+            incentive_count: 0,
+            oracle_bump: bumps.oracle,
+            total_shares: 0,
+            max_lp_supply: 0,
+            min_swap_amount: 0,
+            min_withdraw_lp: 0,
+            successor: None,
+            vault_x_bump: bumps.vault_x,
+            vault_y_bump: bumps.vault_y,
+            min_price: 0,
+            max_price: 0,
+            band_partial_fill: false,
+            discount_mint: Pubkey::default(),
+            discount_tiers: Vec::new(),
+            immutable: false,
+            launch_deposit_cap: 0,
+            launch_deposit_end_ts: 0,
+            withdraw_cooldown_secs: 0,
+            auto_unlock_ts: 0,
+            compliant,
+            lbp_start_weight_x: 0,
+            lbp_end_weight_x: 0,
+            lbp_start_ts: 0,
+            lbp_end_ts: 0,
+            sandwich_guard: false,
+            track_lp_holders,
+            strategy: None,
+            max_deployed_bps: 0,
+            deployed_x: 0,
+            deployed_y: 0,
+            open_time,
+            window_period: 0,
+            window_open_secs: 0,
+            strategy_active_ts: 0,
+            reserved: [0; 32],
         });
-        
+
+        let mut observations = self.observations.load_init()?;
+        observations.config = self.config.key();
+        observations.write(now, 0, 0);
+
+        // Prices stay zero until the first deposit gives the pool reserves
+        let mut oracle = self.oracle.load_init()?;
+        oracle.config = self.config.key();
+        oracle.mint_x = self.mint_x.key();
+        oracle.mint_y = self.mint_y.key();
+        oracle.last_update_ts = now;
+        oracle.last_update_slot = Clock::get()?.slot;
+        oracle.version = PRICE_ORACLE_VERSION;
+        oracle.decimals_x = self.mint_x.decimals;
+        oracle.decimals_y = self.mint_y.decimals;
+
+        self.protocol_stats.total_pools = self.protocol_stats.total_pools.saturating_add(1);
+
         Ok(())
     }
+
+    // Legacy SPL Token mint, or a Token-2022 mint whose transfers call back into this program to keep
+    // the holder registry current. The hook program is fixed for good, it has no authority.
+    fn create_lp_mint(&self, track_lp_holders: bool, bump: u8) -> Result<()> {
+        let (token_program, extensions) = match track_lp_holders {
+            true => (anchor_spl::token_2022::ID, vec![ExtensionType::TransferHook]),
+            false => (anchor_spl::token::ID, vec![]),
+        };
+        require_keys_eq!(self.token_program.key(), token_program, AmmError::InvalidConfig);
+
+        let space = ExtensionType::try_calculate_account_len::<MintState>(&extensions)?;
+        let config = self.config.key();
+        let seeds = [b"lp", config.as_ref(), &[bump]];
+        self.create_pda(&seeds, space)?;
+
+        if track_lp_holders {
+            let cpi_accounts = TransferHookInitialize {
+                token_program_id: self.token_program.to_account_info(),
+                mint: self.mint_lp.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new(self.token_program.to_account_info(), cpi_accounts);
+            transfer_hook_initialize(cpi_ctx, None, Some(crate::ID))?;
+        }
+
+        let cpi_accounts = InitializeMint2 {
+            mint: self.mint_lp.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(self.token_program.to_account_info(), cpi_accounts);
+
+        initialize_mint2(cpi_ctx, 6, &config, Some(&config))
+    }
+
+    // Creates the LP mint account for token_program the way init constraints do, so lamports sent to
+    // the address ahead of time can't block the pool
+    fn create_pda(&self, seeds: &[&[u8]], space: usize) -> Result<()> {
+        let system_program = self.system_program.to_account_info();
+        let signer_seeds = &[seeds];
+        let lamports = Rent::get()?.minimum_balance(space);
+        let current = self.mint_lp.lamports();
+
+        if current == 0 {
+            let cpi_accounts = CreateAccount {
+                from: self.initializer.to_account_info(),
+                to: self.mint_lp.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(system_program, cpi_accounts, signer_seeds);
+
+            return create_account(cpi_ctx, lamports, space as u64, &self.token_program.key());
+        }
+
+        if current < lamports {
+            let cpi_accounts = Transfer {
+                from: self.initializer.to_account_info(),
+                to: self.mint_lp.to_account_info(),
+            };
+            transfer(CpiContext::new(system_program.clone(), cpi_accounts), lamports - current)?;
+        }
+
+        let cpi_accounts = Allocate {
+            account_to_allocate: self.mint_lp.to_account_info(),
+        };
+        allocate(CpiContext::new_with_signer(system_program.clone(), cpi_accounts, signer_seeds), space as u64)?;
+
+        let cpi_accounts = Assign {
+            account_to_assign: self.mint_lp.to_account_info(),
+        };
+        assign(CpiContext::new_with_signer(system_program, cpi_accounts, signer_seeds), &self.token_program.key())
+    }
+
+    // Powers a third party keeps over the pooled tokens. Legacy SPL mints can only carry a freeze
+    // authority, Token-2022 mints can add a permanent delegate or a close authority on top.
+    fn mint_risk_flags(&self) -> Result<u8> {
+        let mut flags = 0;
+
+        for (mint, freeze, delegate, close) in [
+            (&self.mint_x, RISK_FREEZE_AUTHORITY_X, RISK_PERMANENT_DELEGATE_X, RISK_CLOSE_AUTHORITY_X),
+            (&self.mint_y, RISK_FREEZE_AUTHORITY_Y, RISK_PERMANENT_DELEGATE_Y, RISK_CLOSE_AUTHORITY_Y),
+        ] {
+            if mint.freeze_authority.is_some() {
+                flags |= freeze;
+            }
+
+            let info = mint.to_account_info();
+            let data = info.try_borrow_data()?;
+            let state = StateWithExtensions::<MintState>::unpack(&data)?;
+
+            for extension in state.get_extension_types()? {
+                match extension {
+                    // Transfer fees would shrink deposits after the pool priced them, hooks need accounts
+                    // the pool doesn't pass and non-transferable tokens can't be traded at all
+                    ExtensionType::TransferFeeConfig
+                    | ExtensionType::TransferHook
+                    | ExtensionType::NonTransferable => return err!(AmmError::UnsupportedMint),
+                    ExtensionType::PermanentDelegate => flags |= delegate,
+                    ExtensionType::MintCloseAuthority => flags |= close,
+                    _ => {}
+                }
+            }
+        }
+
+        Ok(flags)
+    }
 }
@@ -0,0 +1,181 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{transfer_checked, TransferChecked, Mint, TokenAccount, TokenInterface};
+
+use crate::state::{Config, GlobalConfig};
+use crate::constants::INSURANCE_TIMELOCK_SECS;
+use crate::errors::AmmError;
+
+#[derive(Accounts)]
+pub struct ManageInsurance<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        has_one = authority @ AmmError::Unauthorized,
+        seeds = [b"global"],
+        bump = global_config.bump,
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    pub mint_x: InterfaceAccount<'info, Mint>,
+    pub mint_y: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [
+            b"config",
+            mint_x.key().to_bytes().as_ref(),
+            mint_y.key().to_bytes().as_ref(),
+            config.seed.to_le_bytes().as_ref()
+        ],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+impl<'info> ManageInsurance<'info> {
+    // Replaces any pending proposal, which restarts the timelock
+    pub fn propose_payout(&mut self, amount_x: u64, amount_y: u64, recipient: Pubkey) -> Result<()> {
+        require!(amount_x > 0 || amount_y > 0, AmmError::InvalidAmount);
+        require!(amount_x <= self.config.insurance_x, AmmError::ExceedsAccruedFees);
+        require!(amount_y <= self.config.insurance_y, AmmError::ExceedsAccruedFees);
+
+        self.config.insurance_payout_x = amount_x;
+        self.config.insurance_payout_y = amount_y;
+        self.config.insurance_payout_recipient = recipient;
+        self.config.insurance_payout_ts = Clock::get()?.unix_timestamp + INSURANCE_TIMELOCK_SECS;
+
+        Ok(())
+    }
+
+    pub fn cancel_payout(&mut self) -> Result<()> {
+        require!(self.config.insurance_payout_ts != 0, AmmError::NoPendingPayout);
+
+        self.config.insurance_payout_x = 0;
+        self.config.insurance_payout_y = 0;
+        self.config.insurance_payout_recipient = Pubkey::default();
+        self.config.insurance_payout_ts = 0;
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct ExecuteInsurancePayout<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        has_one = authority @ AmmError::Unauthorized,
+        seeds = [b"global"],
+        bump = global_config.bump,
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    #[account(mint::token_program = token_program_x)]
+    pub mint_x: InterfaceAccount<'info, Mint>,
+    #[account(mint::token_program = token_program_y)]
+    pub mint_y: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [
+            b"config",
+            mint_x.key().to_bytes().as_ref(),
+            mint_y.key().to_bytes().as_ref(),
+            config.seed.to_le_bytes().as_ref()
+        ],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        seeds = [b"vault_x", config.key().as_ref()],
+        bump = config.vault_x_bump,
+    )]
+    pub vault_x: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [b"vault_y", config.key().as_ref()],
+        bump = config.vault_y_bump,
+    )]
+    pub vault_y: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = mint_x,
+        token::authority = config.insurance_payout_recipient,
+    )]
+    pub recipient_x: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = mint_y,
+        token::authority = config.insurance_payout_recipient,
+    )]
+    pub recipient_y: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program_x: Interface<'info, TokenInterface>,
+    pub token_program_y: Interface<'info, TokenInterface>,
+}
+
+impl<'info> ExecuteInsurancePayout<'info> {
+    pub fn execute_payout(&mut self) -> Result<()> {
+        let unlock_ts = self.config.insurance_payout_ts;
+
+        require!(unlock_ts != 0, AmmError::NoPendingPayout);
+        require!(Clock::get()?.unix_timestamp >= unlock_ts, AmmError::TimelockActive);
+
+        let amount_x = self.config.insurance_payout_x;
+        let amount_y = self.config.insurance_payout_y;
+
+        // Fund balances can't have grown smaller since the proposal, but stay defensive
+        self.config.insurance_x = self.config.insurance_x.checked_sub(amount_x).ok_or(AmmError::ExceedsAccruedFees)?;
+        self.config.insurance_y = self.config.insurance_y.checked_sub(amount_y).ok_or(AmmError::ExceedsAccruedFees)?;
+        self.config.insurance_payout_x = 0;
+        self.config.insurance_payout_y = 0;
+        self.config.insurance_payout_recipient = Pubkey::default();
+        self.config.insurance_payout_ts = 0;
+
+        if amount_x > 0 {
+            self.transfer_payout(true, amount_x)?;
+        }
+        if amount_y > 0 {
+            self.transfer_payout(false, amount_y)?;
+        }
+
+        Ok(())
+    }
+
+    fn transfer_payout(&mut self, is_x: bool, amount: u64) -> Result<()> {
+        let (cpi_program, cpi_accounts, mint_decimals) = match is_x {
+            true => (self.token_program_x.to_account_info(), TransferChecked {
+                from: self.vault_x.to_account_info(),
+                mint: self.mint_x.to_account_info(),
+                to: self.recipient_x.to_account_info(),
+                authority: self.config.to_account_info(),
+            }, self.mint_x.decimals),
+
+            false => (self.token_program_y.to_account_info(), TransferChecked {
+                from: self.vault_y.to_account_info(),
+                mint: self.mint_y.to_account_info(),
+                to: self.recipient_y.to_account_info(),
+                authority: self.config.to_account_info(),
+            }, self.mint_y.decimals),
+        };
+
+        let mint_x = self.mint_x.key().to_bytes();
+        let mint_y = self.mint_y.key().to_bytes();
+        let seed = self.config.seed.to_le_bytes();
+
+        let seeds = [
+            b"config",
+            mint_x.as_ref(),
+            mint_y.as_ref(),
+            seed.as_ref(),
+            &[self.config.config_bump]
+        ];
+
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+        transfer_checked(cpi_ctx, amount, mint_decimals)?;
+
+        Ok(())
+    }
+}
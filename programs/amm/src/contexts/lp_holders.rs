@@ -0,0 +1,145 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::spl_token_2022::extension::transfer_hook::TransferHookAccount;
+use anchor_spl::token_2022::spl_token_2022::extension::{BaseStateWithExtensions, StateWithExtensions};
+use anchor_spl::token_2022::spl_token_2022::state::Account as TokenAccountState;
+use anchor_spl::token_interface::{Mint, TokenAccount};
+
+use crate::state::{Config, LpHolders};
+use crate::errors::AmmError;
+use crate::constants::TRANSFER_HOOK_EXECUTE_DISCRIMINATOR;
+
+// Execute discriminator, value length, entry count and a single 35 byte ExtraAccountMeta
+pub const EXTRA_ACCOUNT_METAS_LEN: usize = 8 + 4 + 4 + 35;
+
+// Creates the holder registry and the LP mint's extra account list, which tells Token-2022 to pass
+// the registry to the transfer hook. LP tokens can't be minted or moved until this ran.
+#[derive(Accounts)]
+pub struct InitializeLpHolders<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub mint_x: InterfaceAccount<'info, Mint>,
+    pub mint_y: InterfaceAccount<'info, Mint>,
+    #[account(
+        has_one = mint_x,
+        has_one = mint_y,
+        constraint = config.track_lp_holders @ AmmError::InvalidConfig,
+        seeds = [
+            b"config",
+            mint_x.key().to_bytes().as_ref(),
+            mint_y.key().to_bytes().as_ref(),
+            config.seed.to_le_bytes().as_ref()
+        ],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        seeds = [b"lp", config.key().as_ref()],
+        bump = config.lp_bump,
+    )]
+    pub mint_lp: InterfaceAccount<'info, Mint>,
+    #[account(
+        init,
+        payer = payer,
+        space = LpHolders::LEN,
+        seeds = [b"lp_holders", mint_lp.key().as_ref()],
+        bump
+    )]
+    pub lp_holders: AccountLoader<'info, LpHolders>,
+    /// CHECK: written in init as the SPL transfer hook extra account list of mint_lp
+    #[account(
+        init,
+        payer = payer,
+        space = EXTRA_ACCOUNT_METAS_LEN,
+        seeds = [b"extra-account-metas", mint_lp.key().as_ref()],
+        bump
+    )]
+    pub extra_account_metas: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitializeLpHolders<'info> {
+    pub fn init(&mut self, bumps: &InitializeLpHoldersBumps) -> Result<()> {
+        let mut lp_holders = self.lp_holders.load_init()?;
+        lp_holders.mint_lp = self.mint_lp.key();
+        lp_holders.bump = bumps.lp_holders;
+
+        let info = self.extra_account_metas.to_account_info();
+        let mut data = info.try_borrow_mut_data()?;
+        data.copy_from_slice(&extra_account_metas());
+
+        Ok(())
+    }
+}
+
+// The extra account list in the SPL TLV layout, with the registry as its only entry: a writable PDA
+// of this program seeded by "lp_holders" and the mint, which is account 1 of Execute
+fn extra_account_metas() -> [u8; EXTRA_ACCOUNT_METAS_LEN] {
+    let mut data = [0; EXTRA_ACCOUNT_METAS_LEN];
+    data[..8].copy_from_slice(TRANSFER_HOOK_EXECUTE_DISCRIMINATOR);
+    data[8..12].copy_from_slice(&(4 + 35u32).to_le_bytes());
+    data[12..16].copy_from_slice(&1u32.to_le_bytes());
+
+    let meta = &mut data[16..];
+    meta[0] = 1; // PDA of the hook program
+    // Packed seeds: a literal then an account key, the rest of the 32 bytes zeroed
+    meta[1] = 1;
+    meta[2] = b"lp_holders".len() as u8;
+    meta[3..13].copy_from_slice(b"lp_holders");
+    meta[13] = 3;
+    meta[14] = 1;
+    meta[33] = 0; // is_signer
+    meta[34] = 1; // is_writable
+
+    data
+}
+
+// Called by Token-2022 on every LP transfer of a pool tracking LP holders, after the balances moved.
+// The accounts are the ones of the SPL transfer hook Execute instruction.
+#[derive(Accounts)]
+pub struct TransferHook<'info> {
+    #[account(token::mint = mint)]
+    pub source: InterfaceAccount<'info, TokenAccount>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(token::mint = mint)]
+    pub destination: InterfaceAccount<'info, TokenAccount>,
+    /// CHECK: owner or delegate of source, the transfer already checked it
+    pub authority: UncheckedAccount<'info>,
+    /// CHECK: the mint's extra account list, only read by Token-2022
+    #[account(
+        seeds = [b"extra-account-metas", mint.key().as_ref()],
+        bump,
+    )]
+    pub extra_account_metas: UncheckedAccount<'info>,
+    // Only exists for LP mints of pools tracking holders
+    #[account(
+        mut,
+        seeds = [b"lp_holders", mint.key().as_ref()],
+        bump = lp_holders.load()?.bump,
+    )]
+    pub lp_holders: AccountLoader<'info, LpHolders>,
+}
+
+impl<'info> TransferHook<'info> {
+    pub fn execute(&mut self, amount: u64) -> Result<()> {
+        self.check_transferring()?;
+
+        let mut lp_holders = self.lp_holders.load_mut()?;
+        lp_holders.debit(self.source.owner, amount)?;
+        lp_holders.credit(self.destination.owner, amount)?;
+
+        Ok(())
+    }
+
+    // Token-2022 flags the source account for the duration of the hook call, anyone calling the hook
+    // directly to fake a transfer finds it unset
+    fn check_transferring(&self) -> Result<()> {
+        let info = self.source.to_account_info();
+        let data = info.try_borrow_data()?;
+        let account = StateWithExtensions::<TokenAccountState>::unpack(&data)?;
+        let extension = account.get_extension::<TransferHookAccount>()?;
+
+        require!(bool::from(extension.transferring), AmmError::Unauthorized);
+
+        Ok(())
+    }
+}
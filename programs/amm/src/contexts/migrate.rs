@@ -0,0 +1,356 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::{burn, freeze_account, mint_to, thaw_account, Burn, FreezeAccount, MintTo, ThawAccount, Token};
+use anchor_spl::token_interface::{transfer_checked, TransferChecked, Mint, TokenAccount, TokenInterface};
+
+use crate::state::{Config, WithdrawRequest};
+use crate::errors::AmmError;
+use crate::events::log_slippage;
+use crate::quote::{deposit_amounts, withdraw_amounts};
+
+#[derive(Accounts)]
+pub struct MigrateLiquidity<'info> {
+    #[account(mut)]
+    pub lp_provider: Signer<'info>,
+    #[account(mint::token_program = token_program_x)]
+    pub mint_x: Box<InterfaceAccount<'info, Mint>>,
+    #[account(mint::token_program = token_program_y)]
+    pub mint_y: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        has_one = mint_x,
+        has_one = mint_y,
+        constraint = config.successor == Some(successor.key()) @ AmmError::InvalidSuccessor,
+        seeds = [
+            b"config",
+            mint_x.key().to_bytes().as_ref(),
+            mint_y.key().to_bytes().as_ref(),
+            config.seed.to_le_bytes().as_ref()
+        ],
+        bump = config.config_bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        mut,
+        seeds = [b"lp", config.key().as_ref()],
+        bump = config.lp_bump,
+    )]
+    pub mint_lp: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        seeds = [b"vault_x", config.key().as_ref()],
+        bump = config.vault_x_bump,
+    )]
+    pub vault_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [b"vault_y", config.key().as_ref()],
+        bump = config.vault_y_bump,
+    )]
+    pub vault_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = lp_provider,
+        associated_token::token_program = token_program_x,
+    )]
+    pub lp_provider_ata_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = lp_provider,
+        associated_token::token_program = token_program_y,
+    )]
+    pub lp_provider_ata_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_lp,
+        associated_token::authority = lp_provider,
+    )]
+    pub lp_provider_ata_lp: Box<InterfaceAccount<'info, TokenAccount>>,
+    // The pool the authority designated to take over the liquidity, on the same pair
+    #[account(
+        mut,
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [
+            b"config",
+            mint_x.key().to_bytes().as_ref(),
+            mint_y.key().to_bytes().as_ref(),
+            successor.seed.to_le_bytes().as_ref()
+        ],
+        bump = successor.config_bump,
+    )]
+    pub successor: Box<Account<'info, Config>>,
+    #[account(
+        mut,
+        seeds = [b"lp", successor.key().as_ref()],
+        bump = successor.lp_bump,
+    )]
+    pub successor_mint_lp: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        seeds = [b"vault_x", successor.key().as_ref()],
+        bump = successor.vault_x_bump,
+    )]
+    pub successor_vault_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [b"vault_y", successor.key().as_ref()],
+        bump = successor.vault_y_bump,
+    )]
+    pub successor_vault_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = lp_provider,
+        associated_token::mint = successor_mint_lp,
+        associated_token::authority = lp_provider,
+    )]
+    pub successor_ata_lp: Box<InterfaceAccount<'info, TokenAccount>>,
+    // Required when the old pool has a withdrawal cooldown, migrating out is a withdrawal too
+    #[account(
+        mut,
+        seeds = [b"withdraw_request", config.key().as_ref(), lp_provider.key().as_ref()],
+        bump = withdraw_request.bump,
+    )]
+    pub withdraw_request: Option<Box<Account<'info, WithdrawRequest>>>,
+
+    pub token_program: Program<'info, Token>,
+    pub token_program_x: Interface<'info, TokenInterface>,
+    pub token_program_y: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> MigrateLiquidity<'info> {
+    // Withdraws `lp_amount` from the old pool and deposits the proceeds into the successor in one
+    // instruction, so the LP is never out of the market. Whatever doesn't fit the successor's ratio
+    // is paid out to the LP.
+    pub fn migrate(&mut self, lp_amount: u64, min_lp_out: u64) -> Result<()> {
+        require!(lp_amount > 0, AmmError::InvalidAmount);
+        require!(
+            !self.config.is_dust_withdrawal(lp_amount, self.lp_provider_ata_lp.amount),
+            AmmError::DustAmount
+        );
+        self.consume_withdraw_request(lp_amount)?;
+        // A locked old pool can still be exited this way, that's how authorities retire pools
+        require!(!self.successor.is_locked(Clock::get()?.unix_timestamp), AmmError::AMMLocked);
+        // Migrations mint and burn through the legacy token program and don't update holder registries
+        require!(
+            !self.config.track_lp_holders && !self.successor.track_lp_holders,
+            AmmError::LpHoldersTracked
+        );
+
+        // Exit from the old pool, same math as a withdrawal
+        let (reserve_x, reserve_y) = self.config.reserves(self.vault_x.amount, self.vault_y.amount)?;
+        let quote = withdraw_amounts(&self.config.pool_state(reserve_x, reserve_y, self.mint_lp.supply), lp_amount)
+            .ok_or(AmmError::InsufficientBalance)?;
+        let (x, y) = (quote.amount_x, quote.amount_y);
+        self.config.check_idle(self.vault_x.amount, self.vault_y.amount, x - quote.fee_x, y - quote.fee_y)?;
+
+        self.config.lp_fees_x -= quote.fee_x;
+        self.config.lp_fees_y -= quote.fee_y;
+        self.config.total_shares -= quote.shares;
+
+        // Entry into the successor, same math as a deposit
+        let (lp_out, deposit_x, deposit_y) = self.successor_deposit(x, y)?;
+
+        if lp_out < min_lp_out {
+            log_slippage(self.config.key(), self.lp_provider.key(), self.successor_mint_lp.key(), lp_out, min_lp_out);
+        }
+        require!(lp_out > 0 && lp_out >= min_lp_out, AmmError::SlippageExceeded);
+        require!(
+            self.successor.max_lp_supply == 0
+                || self.successor_mint_lp.supply.saturating_add(lp_out) <= self.successor.max_lp_supply,
+            AmmError::TvlCapExceeded
+        );
+        // Launch caps are tracked per deposit, migrations would bypass them
+        require!(!self.successor.launch_cap_active(Clock::get()?.unix_timestamp), AmmError::LaunchCapExceeded);
+
+        let remaining_lp = self.lp_provider_ata_lp.amount.checked_sub(lp_amount).ok_or(AmmError::InsufficientBalance)?;
+        if self.config.soulbound && self.lp_provider_ata_lp.is_frozen() {
+            self.set_lp_frozen(false, false)?;
+        }
+        self.burn_lp_tokens(lp_amount)?;
+        if self.config.soulbound && remaining_lp > 0 {
+            self.set_lp_frozen(false, true)?;
+        }
+
+        self.transfer_from_vault(true, true, deposit_x)?;
+        self.transfer_from_vault(false, true, deposit_y)?;
+        if x > deposit_x {
+            self.transfer_from_vault(true, false, x - deposit_x)?;
+        }
+        if y > deposit_y {
+            self.transfer_from_vault(false, false, y - deposit_y)?;
+        }
+
+        if self.successor.soulbound && self.successor_ata_lp.is_frozen() {
+            self.set_lp_frozen(true, false)?;
+        }
+        self.mint_successor_lp(lp_out)?;
+        if self.successor.soulbound {
+            self.set_lp_frozen(true, true)?;
+        }
+
+        Ok(())
+    }
+
+    fn consume_withdraw_request(&mut self, lp_amount: u64) -> Result<()> {
+        if self.config.withdraw_cooldown_secs == 0 {
+            return Ok(());
+        }
+
+        let request = self.withdraw_request.as_mut().ok_or(AmmError::WithdrawNotRequested)?;
+
+        request.consume(lp_amount, self.lp_provider_ata_lp.amount, Clock::get()?.unix_timestamp)
+    }
+
+    // LP tokens the successor mints for up to `x` and `y`, and the amounts it takes for them. An
+    // empty successor takes everything and locks MINIMUM_LIQUIDITY like any first deposit.
+    fn successor_deposit(&mut self, x: u64, y: u64) -> Result<(u64, u64, u64)> {
+        let successor: &mut Config = &mut self.successor;
+        let (reserve_x, reserve_y) = successor.reserves(self.successor_vault_x.amount, self.successor_vault_y.amount)?;
+
+        let quote = deposit_amounts(&successor.pool_state(reserve_x, reserve_y, self.successor_mint_lp.supply), x, y)
+            .ok_or(AmmError::InvalidAmount)?;
+
+        successor.lp_fees_x = successor.lp_fees_x.checked_add(quote.fee_x).ok_or(AmmError::InvalidAmount)?;
+        successor.lp_fees_y = successor.lp_fees_y.checked_add(quote.fee_y).ok_or(AmmError::InvalidAmount)?;
+        successor.total_shares = successor.total_shares.checked_add(quote.shares).ok_or(AmmError::InvalidAmount)?;
+
+        Ok((quote.lp_amount, quote.amount_x, quote.amount_y))
+    }
+
+    // From the old pool's vaults into the successor's vaults, or back to the LP
+    fn transfer_from_vault(&mut self, is_x: bool, to_successor: bool, amount: u64) -> Result<()> {
+        let (from, to) = match (is_x, to_successor) {
+            (true, true) => (&self.vault_x, &self.successor_vault_x),
+            (true, false) => (&self.vault_x, &self.lp_provider_ata_x),
+            (false, true) => (&self.vault_y, &self.successor_vault_y),
+            (false, false) => (&self.vault_y, &self.lp_provider_ata_y),
+        };
+        let (cpi_program, mint, mint_decimals) = match is_x {
+            true => (self.token_program_x.to_account_info(), &self.mint_x, self.mint_x.decimals),
+            false => (self.token_program_y.to_account_info(), &self.mint_y, self.mint_y.decimals),
+        };
+
+        let cpi_accounts = TransferChecked {
+            from: from.to_account_info(),
+            mint: mint.to_account_info(),
+            to: to.to_account_info(),
+            authority: self.config.to_account_info(),
+        };
+
+        let mint_x = self.mint_x.key().to_bytes();
+        let mint_y = self.mint_y.key().to_bytes();
+        let seed = self.config.seed.to_le_bytes();
+
+        let seeds = [
+            b"config",
+            mint_x.as_ref(),
+            mint_y.as_ref(),
+            seed.as_ref(),
+            &[self.config.config_bump]
+        ];
+
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+        transfer_checked(cpi_ctx, amount, mint_decimals)?;
+
+        Ok(())
+    }
+
+    fn burn_lp_tokens(&mut self, amount: u64) -> Result<()> {
+        let cpi_program = self.token_program.to_account_info();
+
+        let cpi_accounts = Burn {
+            mint: self.mint_lp.to_account_info(),
+            from: self.lp_provider_ata_lp.to_account_info(),
+            authority: self.lp_provider.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+
+        burn(cpi_ctx, amount)?;
+
+        Ok(())
+    }
+
+    fn mint_successor_lp(&mut self, amount: u64) -> Result<()> {
+        let cpi_program = self.token_program.to_account_info();
+
+        let cpi_accounts = MintTo {
+            mint: self.successor_mint_lp.to_account_info(),
+            to: self.successor_ata_lp.to_account_info(),
+            authority: self.successor.to_account_info(),
+        };
+
+        let mint_x = self.mint_x.key().to_bytes();
+        let mint_y = self.mint_y.key().to_bytes();
+        let seed = self.successor.seed.to_le_bytes();
+
+        let seeds = [
+            b"config",
+            mint_x.as_ref(),
+            mint_y.as_ref(),
+            seed.as_ref(),
+            &[self.successor.config_bump]
+        ];
+
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+        mint_to(cpi_ctx, amount)?;
+
+        Ok(())
+    }
+
+    // Soulbound pools keep LP accounts frozen, so they are thawed only for the duration of a mint or burn
+    fn set_lp_frozen(&mut self, successor: bool, frozen: bool) -> Result<()> {
+        let cpi_program = self.token_program.to_account_info();
+
+        let (config, account, mint) = match successor {
+            true => (&self.successor, &self.successor_ata_lp, &self.successor_mint_lp),
+            false => (&self.config, &self.lp_provider_ata_lp, &self.mint_lp),
+        };
+
+        let mint_x = self.mint_x.key().to_bytes();
+        let mint_y = self.mint_y.key().to_bytes();
+        let seed = config.seed.to_le_bytes();
+
+        let seeds = [
+            b"config",
+            mint_x.as_ref(),
+            mint_y.as_ref(),
+            seed.as_ref(),
+            &[config.config_bump]
+        ];
+
+        let signer_seeds = &[&seeds[..]];
+
+        match frozen {
+            true => {
+                let cpi_accounts = FreezeAccount {
+                    account: account.to_account_info(),
+                    mint: mint.to_account_info(),
+                    authority: config.to_account_info(),
+                };
+                freeze_account(CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds))?;
+            }
+            false => {
+                let cpi_accounts = ThawAccount {
+                    account: account.to_account_info(),
+                    mint: mint.to_account_info(),
+                    authority: config.to_account_info(),
+                };
+                thaw_account(CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds))?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -2,8 +2,48 @@ pub mod deposit;
 pub mod swap;
 pub mod withdraw;
 pub mod init;
+pub mod global;
+pub mod claim_protocol_fees;
+pub mod update_observation;
+pub mod view;
+pub mod update_config;
+pub mod epoch;
+pub mod insurance;
+pub mod incentive;
+pub mod migrate;
+pub mod rescue;
+pub mod cooldown;
+pub mod compliance;
+pub mod swap_best;
+pub mod gc;
+pub mod harvest;
+pub mod lp_holders;
+pub mod strategy;
+pub mod resize;
+#[cfg(feature = "devnet")]
+pub mod devnet;
 
 pub use deposit::*;
 pub use swap::*;
 pub use withdraw::*;
-pub use init::*;
\ No newline at end of file
+pub use init::*;
+pub use global::*;
+pub use claim_protocol_fees::*;
+pub use update_observation::*;
+pub use view::*;
+pub use update_config::*;
+pub use epoch::*;
+pub use insurance::*;
+pub use incentive::*;
+pub use migrate::*;
+pub use rescue::*;
+pub use cooldown::*;
+pub use compliance::*;
+pub use swap_best::*;
+pub use gc::*;
+pub use harvest::*;
+pub use lp_holders::*;
+pub use strategy::*;
+pub use resize::*;
+#[cfg(feature = "devnet")]
+pub use devnet::*;
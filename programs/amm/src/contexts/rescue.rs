@@ -0,0 +1,96 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{transfer_checked, TransferChecked, Mint, TokenAccount, TokenInterface};
+
+use crate::state::{Config, GlobalConfig};
+use crate::errors::AmmError;
+
+#[derive(Accounts)]
+pub struct RescueTokens<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        has_one = authority @ AmmError::Unauthorized,
+        seeds = [b"global"],
+        bump = global_config.bump,
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    pub mint_x: InterfaceAccount<'info, Mint>,
+    pub mint_y: InterfaceAccount<'info, Mint>,
+    #[account(
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [
+            b"config",
+            mint_x.key().to_bytes().as_ref(),
+            mint_y.key().to_bytes().as_ref(),
+            config.seed.to_le_bytes().as_ref()
+        ],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        seeds = [b"lp", config.key().as_ref()],
+        bump = config.lp_bump,
+    )]
+    pub mint_lp: InterfaceAccount<'info, Mint>,
+    // The vault mints and the LP mint (held by the stake vault) are never rescuable
+    #[account(
+        constraint = mint.key() != mint_x.key()
+            && mint.key() != mint_y.key()
+            && mint.key() != mint_lp.key() @ AmmError::MintNotRescuable,
+        mint::token_program = token_program,
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+    // Any token account the config PDA owns for the stray mint, not only its ATA
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = config,
+    )]
+    pub source: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = authority,
+    )]
+    pub receiver: InterfaceAccount<'info, TokenAccount>,
+
+    // Program of the stray mint, legacy SPL Token or Token-2022
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> RescueTokens<'info> {
+    // Sweeps tokens sent to the config PDA by mistake to the protocol treasury
+    pub fn rescue(&mut self) -> Result<()> {
+        let amount = self.source.amount;
+        require!(amount > 0, AmmError::InvalidAmount);
+
+        let cpi_program = self.token_program.to_account_info();
+
+        let cpi_accounts = TransferChecked {
+            from: self.source.to_account_info(),
+            mint: self.mint.to_account_info(),
+            to: self.receiver.to_account_info(),
+            authority: self.config.to_account_info(),
+        };
+
+        let mint_x = self.mint_x.key().to_bytes();
+        let mint_y = self.mint_y.key().to_bytes();
+        let seed = self.config.seed.to_le_bytes();
+
+        let seeds = [
+            b"config",
+            mint_x.as_ref(),
+            mint_y.as_ref(),
+            seed.as_ref(),
+            &[self.config.config_bump]
+        ];
+
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+        transfer_checked(cpi_ctx, amount, self.mint.decimals)?;
+
+        Ok(())
+    }
+}
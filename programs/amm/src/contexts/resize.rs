@@ -0,0 +1,162 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+
+use crate::state::Config;
+use crate::errors::AmmError;
+
+// Grows a pool's Config created before fields outgrew Config::reserved. The strategy fields needed
+// more than the 9 reserved bytes left, so Config grew by 106 bytes. Borsh has no fixed offsets and
+// Anchor doesn't clear what a shorter cpi_programs or discount_tiers list leaves behind, so in an old
+// account the new fields past its 9 reserved bytes read stale list bytes. Resizing reads the old
+// layout, resets everything added since and re-serializes it into a zeroed account. Pools created
+// before have to be resized after the upgrade, before anything else uses them. This is permissionless.
+#[derive(Accounts)]
+pub struct ResizeConfig<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: an older layout of Config that can't be deserialized yet, checked in resize
+    #[account(mut, owner = crate::ID)]
+    pub config: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ResizeConfig<'info> {
+    pub fn resize(&mut self) -> Result<()> {
+        let info = self.config.to_account_info();
+        // A grown account holds real values in the new fields
+        require!(info.data_len() < Config::LEN, AmmError::InvalidConfig);
+        let config = read_legacy(&info.try_borrow_data()?)?;
+
+        let rent = Rent::get()?.minimum_balance(Config::LEN);
+        if info.lamports() < rent {
+            let cpi_accounts = Transfer {
+                from: self.payer.to_account_info(),
+                to: info.clone(),
+            };
+            transfer(CpiContext::new(self.system_program.to_account_info(), cpi_accounts), rent - info.lamports())?;
+        }
+        info.resize(Config::LEN)?;
+
+        let mut data = info.try_borrow_mut_data()?;
+        data.fill(0);
+        config.try_serialize(&mut &mut data[..])?;
+
+        Ok(())
+    }
+}
+
+// An old Config with the fields added since at their defaults. Its serialized fields end with the 9
+// zero reserved bytes, so the new fields start on a zero strategy tag and only the bytes after may
+// be stale.
+fn read_legacy(data: &[u8]) -> Result<Config> {
+    let mut grown = data.to_vec();
+    grown.resize(Config::LEN, 0);
+    let mut config = Config::try_deserialize(&mut &grown[..])?;
+
+    config.strategy = None;
+    config.max_deployed_bps = 0;
+    config.deployed_x = 0;
+    config.deployed_y = 0;
+    config.open_time = 0;
+    config.window_period = 0;
+    config.window_open_secs = 0;
+    config.strategy_active_ts = 0;
+    config.reserved = [0; 32];
+
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{DiscountTier, MAX_CPI_PROGRAMS, MAX_DISCOUNT_TIERS};
+
+    // Config::LEN before the strategy fields grew it
+    const LEGACY_LEN: usize = Config::LEN - 106;
+
+    fn config() -> Config {
+        let mut config = Config::deserialize(&mut &[0u8; Config::INIT_SPACE][..]).unwrap();
+        config.authority = Some(Pubkey::new_unique());
+        config.successor = Some(Pubkey::new_unique());
+        config.cpi_programs = vec![Pubkey::new_from_array([7; 32]); MAX_CPI_PROGRAMS];
+        config.discount_mint = Pubkey::new_from_array([9; 32]);
+        config.discount_tiers = vec![DiscountTier { min_balance: 1, discount: 1 }; MAX_DISCOUNT_TIERS];
+        config.fee = 30;
+
+        config
+    }
+
+    // What an old program serialized `config` to: the fields added since, all default here, take the
+    // place of the old 9 reserved bytes
+    fn legacy_bytes(config: &Config) -> Vec<u8> {
+        let mut data = Vec::new();
+        config.try_serialize(&mut data).unwrap();
+        data.truncate(data.len() - 83);
+        data.extend_from_slice(&[0; 9]);
+
+        data
+    }
+
+    // An old pool that filled its lists, as created
+    fn legacy_account() -> Vec<u8> {
+        let data = legacy_bytes(&config());
+        assert_eq!(data.len(), LEGACY_LEN);
+
+        data
+    }
+
+    // The same pool after set_cpi_policy emptied its list, with the old tail left behind
+    fn shrunk_legacy_account() -> Vec<u8> {
+        let mut config = config();
+        config.cpi_programs.clear();
+
+        let mut data = legacy_account();
+        let shrunk = legacy_bytes(&config);
+        data[..shrunk.len()].copy_from_slice(&shrunk);
+
+        data
+    }
+
+    // What resize leaves in the account
+    fn resized(data: &[u8]) -> Config {
+        let config = read_legacy(data).unwrap();
+        let mut account = vec![0; Config::LEN];
+        config.try_serialize(&mut &mut account[..]).unwrap();
+
+        Config::try_deserialize(&mut &account[..]).unwrap()
+    }
+
+    #[test]
+    fn full_old_configs_load_once_grown() {
+        let data = legacy_account();
+        assert!(Config::try_deserialize(&mut &data[..]).is_err());
+
+        let config = resized(&data);
+        assert_eq!(config.fee, 30);
+        assert_eq!(config.discount_tiers.len(), MAX_DISCOUNT_TIERS);
+        assert!(config.strategy.is_none());
+        assert_eq!((config.deployed_x, config.deployed_y), (0, 0));
+        assert_eq!((config.open_time, config.window_period), (0, 0));
+    }
+
+    #[test]
+    fn stale_list_bytes_are_dropped() {
+        let data = shrunk_legacy_account();
+
+        // Read in place, the new fields pick up the old list's pubkeys
+        let mut grown = data.clone();
+        grown.resize(Config::LEN, 0);
+        let stale = Config::try_deserialize(&mut &grown[..]).unwrap();
+        assert_ne!((stale.deployed_x, stale.deployed_y, stale.open_time), (0, 0, 0));
+
+        let config = resized(&data);
+        assert!(config.cpi_programs.is_empty());
+        assert_eq!(config.discount_tiers.len(), MAX_DISCOUNT_TIERS);
+        assert!(config.strategy.is_none());
+        assert_eq!(config.max_deployed_bps, 0);
+        assert_eq!((config.deployed_x, config.deployed_y), (0, 0));
+        assert_eq!((config.open_time, config.window_period, config.window_open_secs), (0, 0, 0));
+        assert_eq!(config.strategy_active_ts, 0);
+        assert_eq!(config.reserved, [0; 32]);
+    }
+}
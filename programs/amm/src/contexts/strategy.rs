@@ -0,0 +1,188 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{transfer_checked, TransferChecked, Mint, TokenAccount, TokenInterface};
+
+use crate::state::Config;
+use crate::events::IdleLiquidityMoved;
+use crate::errors::AmmError;
+
+// Moves reserves between the vaults and a token account of the pool's strategy. The pool authority
+// deploys, the strategy signs to return them.
+#[derive(Accounts)]
+pub struct IdleLiquidity<'info> {
+    pub signer: Signer<'info>,
+    #[account(mint::token_program = token_program_x)]
+    pub mint_x: InterfaceAccount<'info, Mint>,
+    #[account(mint::token_program = token_program_y)]
+    pub mint_y: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [
+            b"config",
+            mint_x.key().to_bytes().as_ref(),
+            mint_y.key().to_bytes().as_ref(),
+            config.seed.to_le_bytes().as_ref()
+        ],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        seeds = [b"vault_x", config.key().as_ref()],
+        bump = config.vault_x_bump,
+    )]
+    pub vault_x: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [b"vault_y", config.key().as_ref()],
+        bump = config.vault_y_bump,
+    )]
+    pub vault_y: InterfaceAccount<'info, TokenAccount>,
+    // Any token account of the strategy in the moved token
+    #[account(
+        mut,
+        constraint = config.strategy == Some(strategy_account.owner) @ AmmError::InvalidConfig,
+    )]
+    pub strategy_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program_x: Interface<'info, TokenInterface>,
+    pub token_program_y: Interface<'info, TokenInterface>,
+}
+
+impl<'info> IdleLiquidity<'info> {
+    // Lends `amount` of the X or Y reserves to the strategy, up to max_deployed_bps of the reserve.
+    // Deployed tokens keep counting in the reserves, so pricing and LP shares don't move.
+    pub fn deploy(&mut self, is_x: bool, amount: u64) -> Result<()> {
+        require!(self.config.authority == Some(self.signer.key()), AmmError::Unauthorized);
+        require!(!self.config.immutable, AmmError::ConfigImmutable);
+        require!(amount > 0, AmmError::InvalidAmount);
+        require!(Clock::get()?.unix_timestamp >= self.config.strategy_active_ts, AmmError::TimelockActive);
+        self.check_strategy_mint(is_x)?;
+
+        let (reserve_x, reserve_y) = self.config.reserves(self.vault_x.amount, self.vault_y.amount)?;
+        let (out_x, out_y, reserve, deployed) = match is_x {
+            true => (amount, 0, reserve_x, self.config.deployed_x),
+            false => (0, amount, reserve_y, self.config.deployed_y),
+        };
+        self.config.check_idle(self.vault_x.amount, self.vault_y.amount, out_x, out_y)?;
+
+        let deployed = deployed.checked_add(amount).ok_or(AmmError::InvalidAmount)?;
+        let limit = reserve as u128 * self.config.max_deployed_bps as u128 / 10_000;
+        require!(deployed as u128 <= limit, AmmError::DeployLimitExceeded);
+
+        match is_x {
+            true => self.config.deployed_x = deployed,
+            false => self.config.deployed_y = deployed,
+        }
+        self.transfer_from_vault(is_x, amount)?;
+
+        self.emit_moved(is_x, amount, true);
+
+        Ok(())
+    }
+
+    // Returns `amount` to the vault. Anything beyond what was deployed is yield and stays in the
+    // reserves, earned by the LPs.
+    pub fn recall(&mut self, is_x: bool, amount: u64) -> Result<()> {
+        require!(self.config.strategy == Some(self.signer.key()), AmmError::Unauthorized);
+        require!(amount > 0, AmmError::InvalidAmount);
+        self.check_strategy_mint(is_x)?;
+
+        self.transfer_to_vault(is_x, amount)?;
+
+        let deployed = match is_x {
+            true => &mut self.config.deployed_x,
+            false => &mut self.config.deployed_y,
+        };
+        *deployed = deployed.saturating_sub(amount);
+
+        self.emit_moved(is_x, amount, false);
+
+        Ok(())
+    }
+
+    fn check_strategy_mint(&self, is_x: bool) -> Result<()> {
+        let mint = match is_x {
+            true => self.mint_x.key(),
+            false => self.mint_y.key(),
+        };
+        require_keys_eq!(self.strategy_account.mint, mint, AmmError::InvalidConfig);
+
+        Ok(())
+    }
+
+    fn emit_moved(&self, is_x: bool, amount: u64, deployed: bool) {
+        emit!(IdleLiquidityMoved {
+            config: self.config.key(),
+            strategy: self.strategy_account.owner,
+            is_x,
+            amount,
+            deployed,
+            deployed_x: self.config.deployed_x,
+            deployed_y: self.config.deployed_y,
+        });
+    }
+
+    fn transfer_from_vault(&mut self, is_x: bool, amount: u64) -> Result<()> {
+        let (cpi_program, cpi_accounts, mint_decimals) = match is_x {
+            true => (self.token_program_x.to_account_info(), TransferChecked {
+                from: self.vault_x.to_account_info(),
+                mint: self.mint_x.to_account_info(),
+                to: self.strategy_account.to_account_info(),
+                authority: self.config.to_account_info(),
+            }, self.mint_x.decimals),
+
+            false => (self.token_program_y.to_account_info(), TransferChecked {
+                from: self.vault_y.to_account_info(),
+                mint: self.mint_y.to_account_info(),
+                to: self.strategy_account.to_account_info(),
+                authority: self.config.to_account_info(),
+            }, self.mint_y.decimals),
+        };
+
+        let mint_x = self.mint_x.key().to_bytes();
+        let mint_y = self.mint_y.key().to_bytes();
+        let seed = self.config.seed.to_le_bytes();
+
+        let seeds = [
+            b"config",
+            mint_x.as_ref(),
+            mint_y.as_ref(),
+            seed.as_ref(),
+            &[self.config.config_bump]
+        ];
+
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+        transfer_checked(cpi_ctx, amount, mint_decimals)?;
+
+        Ok(())
+    }
+
+    fn transfer_to_vault(&mut self, is_x: bool, amount: u64) -> Result<()> {
+        let (cpi_program, cpi_accounts, mint_decimals) = match is_x {
+            true => (self.token_program_x.to_account_info(), TransferChecked {
+                from: self.strategy_account.to_account_info(),
+                mint: self.mint_x.to_account_info(),
+                to: self.vault_x.to_account_info(),
+                authority: self.signer.to_account_info(),
+            }, self.mint_x.decimals),
+
+            false => (self.token_program_y.to_account_info(), TransferChecked {
+                from: self.strategy_account.to_account_info(),
+                mint: self.mint_y.to_account_info(),
+                to: self.vault_y.to_account_info(),
+                authority: self.signer.to_account_info(),
+            }, self.mint_y.decimals),
+        };
+
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+
+        transfer_checked(cpi_ctx, amount, mint_decimals)?;
+
+        Ok(())
+    }
+}
@@ -1,22 +1,58 @@
 use anchor_lang::prelude::*;
-use anchor_spl::{associated_token::AssociatedToken, token::{transfer_checked, TransferChecked, Token, Mint, TokenAccount}};
-use constant_product_curve::{ConstantProduct, LiquidityPair, SwapResult};
+use anchor_lang::solana_program::program_option::COption;
+use anchor_lang::solana_program::instruction::{get_stack_height, TRANSACTION_LEVEL_STACK_HEIGHT};
+use anchor_lang::solana_program::sysvar::instructions::{self as instructions_sysvar, load_current_index_checked, load_instruction_at_checked};
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::Token;
+use anchor_spl::token_interface::{transfer_checked, TransferChecked, Mint, TokenAccount, TokenInterface};
+use constant_product_curve::SwapResult;
 
-use crate::state::Config;
+use crate::state::{Config, GlobalConfig, Observations, PriceOracle, ProtocolStats};
 use crate::errors::AmmError;
+use crate::events::{log_slippage, ExactOutputFilled, SwapExecuted};
+use crate::math::{mul_div_wide, q64_price};
+use crate::quote::input_for_output;
+use crate::contexts::{SWAP_BEST_FIXED_ACCOUNTS, SWAP_BEST_POOL_ACCOUNTS};
+
+// Position of config among the Swap accounts, for reading other swaps from the instructions sysvar
+const SWAP_CONFIG_ACCOUNT: usize = 4;
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 pub struct SwapArgs {
     pub is_x: bool,
     pub amount: u64, 
     pub min: u64,
+    pub platform_fee: u16, // frontend fee in basis points of `amount`, paid to platform_fee_receiver
+}
+
+// Outcome of an exact-output swap, returned to the caller
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ExactOutputFill {
+    pub amount_in: u64, // deposited into the pool
+    pub amount_out: u64, // sent to the user, the requested amount unless partially filled
+    pub unfilled: u64, // part of the requested amount that wasn't bought
 }
 
 #[derive(Accounts)]
 pub struct Swap<'info> {
+    // The owner of the token accounts, or a delegate approved on the input one
     #[account(mut)]
     pub user: Signer<'info>,
+    /// CHECK: only used as the authority of the token accounts, the output always goes to the owner
+    pub owner: UncheckedAccount<'info>,
+    #[account(
+        seeds = [b"global"],
+        bump = global_config.bump,
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    #[account(
+        mut,
+        seeds = [b"stats"],
+        bump = protocol_stats.bump,
+    )]
+    pub protocol_stats: Account<'info, ProtocolStats>,
     #[account(
+        mut,
         has_one = mint_x,
         has_one = mint_y,
         seeds = [
@@ -27,44 +63,70 @@ pub struct Swap<'info> {
         ],
         bump = config.config_bump,
     )]
-    pub config: Account<'info, Config>,   
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        seeds = [b"observations", config.key().as_ref()],
+        bump = config.observations_bump,
+    )]
+    pub observations: AccountLoader<'info, Observations>,
+    #[account(
+        mut,
+        seeds = [b"oracle", config.key().as_ref()],
+        bump = config.oracle_bump,
+    )]
+    pub oracle: AccountLoader<'info, PriceOracle>,
     #[account(
         seeds = [b"lp", config.key().as_ref()],
         bump = config.lp_bump,
         mint::decimals = 6,
         mint::authority = config
     )]
-    pub mint_lp: Account<'info, Mint>, 
-    pub mint_x: Account<'info, Mint>,
-    pub mint_y: Account<'info, Mint>, 
+    pub mint_lp: InterfaceAccount<'info, Mint>, 
+    #[account(mint::token_program = token_program_x)]
+    pub mint_x: InterfaceAccount<'info, Mint>,
+    #[account(mint::token_program = token_program_y)]
+    pub mint_y: InterfaceAccount<'info, Mint>, 
     #[account(
         mut,
-        associated_token::mint = mint_x,
-        associated_token::authority = config,
+        seeds = [b"vault_x", config.key().as_ref()],
+        bump = config.vault_x_bump,
     )]
-    pub vault_x: Account<'info, TokenAccount>,
+    pub vault_x: InterfaceAccount<'info, TokenAccount>,
     #[account(
         mut,
-        associated_token::mint = mint_y,
-        associated_token::authority = config,
+        seeds = [b"vault_y", config.key().as_ref()],
+        bump = config.vault_y_bump,
     )]
-    pub vault_y: Account<'info, TokenAccount>,
+    pub vault_y: InterfaceAccount<'info, TokenAccount>,
     #[account(
         init_if_needed,
         payer = user,
         associated_token::mint = mint_x,
-        associated_token::authority = user,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program_x,
     )]
-    pub user_ata_x: Account<'info, TokenAccount>,
+    pub user_ata_x: InterfaceAccount<'info, TokenAccount>,
     #[account(
         init_if_needed,
         payer = user,
         associated_token::mint = mint_y,
-        associated_token::authority = user,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program_y,
     )]
-    pub user_ata_y: Account<'info, TokenAccount>,
+    pub user_ata_y: InterfaceAccount<'info, TokenAccount>,
+    // Frontend's token account for the input mint, required when a platform fee is charged
+    #[account(mut)]
+    pub platform_fee_receiver: Option<InterfaceAccount<'info, TokenAccount>>,
+    // Owner's token account for the pool's discount mint, when claiming a fee discount tier
+    pub discount_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: the instructions sysvar, used to find the program that invoked swap through CPI
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions: UncheckedAccount<'info>,
 
     pub token_program: Program<'info, Token>,
+    pub token_program_x: Interface<'info, TokenInterface>,
+    pub token_program_y: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
     pub associated_token_program: Program<'info, AssociatedToken>,
 }
@@ -72,29 +134,129 @@ pub struct Swap<'info> {
 impl<'info> Swap<'info> {
     pub fn swap(&mut self, args: SwapArgs) -> Result<()> {
         require!(args.amount > 0, AmmError::InvalidAmount);
-        require!(self.config.locked == false, AmmError::AMMLocked);
+        require!(args.amount >= self.config.min_swap_amount, AmmError::DustAmount);
+        let (reserve_x, reserve_y) = self.begin_swap(args.is_x, args.amount)?;
 
-        // FIXED: Check for zero balance before creating curve
-        require!(self.vault_x.amount > 0 && self.vault_y.amount > 0, AmmError::InsufficientBalance);
-        require!(self.mint_lp.supply > 0, AmmError::InsufficientBalance);
+        // The platform fee comes off the top of the input, the pool only trades the rest
+        let mut platform_fee = self.platform_fee_amount(&args)?;
+        let mut swap_amount = args.amount - platform_fee;
+
+        let fee = self.config.swap_fee(args.is_x, Clock::get()?.slot).saturating_sub(self.fee_discount()?);
+
+        // Shrink the trade to what the price band allows, with the platform fee scaled down alike
+        let partial_fill = self.config.band_partial_fill;
+        let band_amount = self.band_fill_amount(args.is_x, reserve_x, reserve_y, fee, swap_amount, partial_fill);
+        if band_amount < swap_amount {
+            platform_fee = (platform_fee as u128 * band_amount as u128 / swap_amount as u128) as u64;
+            swap_amount = band_amount;
+        }
+        require_neq!(swap_amount, 0, AmmError::PriceOutOfBand);
+
+        // Fees round up and the output rounds down, so every rounding error stays in the pool
+        let quote = self.config
+            .swap_quote(args.is_x, reserve_x, reserve_y, swap_amount, fee, Clock::get()?.unix_timestamp)
+            .ok_or(AmmError::InvalidAmount)?;
+        let res = SwapResult { deposit: quote.amount_in, withdraw: quote.amount_out, fee: quote.fee };
+
+        if res.withdraw < args.min {
+            let mint_out = match args.is_x {
+                true => self.mint_y.key(),
+                false => self.mint_x.key(),
+            };
+            log_slippage(self.config.key(), self.user.key(), mint_out, res.withdraw, args.min);
+        }
+        require!(res.withdraw >= args.min, AmmError::SlippageExceeded);
+
+        self.execute(args, res, platform_fee, reserve_x, reserve_y)
+    }
 
-        let mut curve = ConstantProduct::init(
-            self.vault_x.amount,
-            self.vault_y.amount,
-            self.mint_lp.supply,
-            self.config.fee, 
-            None,
-        ).map_err(|e| AmmError::from(e))?; // FIXED: Handle error properly
+    // Buys exactly `amount_out` for at most `max_in`. With `allow_partial`, a trade that max_in or
+    // the price band can't cover in full buys as much as they allow instead of failing, and the
+    // unfilled rest is returned and emitted. No platform fee is taken.
+    pub fn swap_exact_out(&mut self, is_x: bool, amount_out: u64, max_in: u64, allow_partial: bool) -> Result<ExactOutputFill> {
+        require!(amount_out > 0 && max_in > 0, AmmError::InvalidAmount);
+        let (reserve_x, reserve_y) = self.begin_swap(is_x, max_in)?;
 
-        let p = match args.is_x {
-            true => LiquidityPair::X,
-            false => LiquidityPair::Y,
+        let now = Clock::get()?.unix_timestamp;
+        let fee = self.config.swap_fee(is_x, Clock::get()?.slot).saturating_sub(self.fee_discount()?);
+        let limit = self.band_fill_amount(is_x, reserve_x, reserve_y, fee, max_in, allow_partial);
+
+        let config: &Config = &self.config;
+        let quote_for = |amount: u64| config.swap_quote(is_x, reserve_x, reserve_y, amount, fee, now);
+
+        let amount_in = match input_for_output(amount_out, limit, quote_for) {
+            Some(amount_in) => amount_in,
+            None if allow_partial => limit,
+            None => {
+                let mint_out = match is_x {
+                    true => self.mint_y.key(),
+                    false => self.mint_x.key(),
+                };
+                let available = quote_for(limit).map_or(0, |quote| quote.amount_out);
+                log_slippage(self.config.key(), self.user.key(), mint_out, available, amount_out);
+
+                return err!(AmmError::SlippageExceeded);
+            },
+        };
+        require_neq!(amount_in, 0, AmmError::PriceOutOfBand);
+        require!(amount_in >= self.config.min_swap_amount, AmmError::DustAmount);
+
+        // The search settles on the smallest input covering amount_out, whatever it pays beyond
+        // that by rounding stays in the pool
+        let quote = quote_for(amount_in).ok_or(AmmError::InvalidAmount)?;
+        let res = SwapResult { deposit: quote.amount_in, withdraw: quote.amount_out.min(amount_out), fee: quote.fee };
+        let fill = ExactOutputFill {
+            amount_in: res.deposit,
+            amount_out: res.withdraw,
+            unfilled: amount_out - res.withdraw,
         };
 
-        let res = curve.swap(p, args.amount, args.min).map_err(|e| AmmError::from(e))?;
+        let args = SwapArgs { is_x, amount: res.deposit, min: amount_out, platform_fee: 0 };
+        self.execute(args, res, 0, reserve_x, reserve_y)?;
+
+        emit!(ExactOutputFilled {
+            config: self.config.key(),
+            user: self.user.key(),
+            is_x,
+            amount_in: fill.amount_in,
+            amount_out: fill.amount_out,
+            unfilled: fill.unfilled,
+        });
+
+        Ok(fill)
+    }
+
+    // Checks every swap runs before trading, returns the reserves it trades against
+    fn begin_swap(&mut self, is_x: bool, max_in: u64) -> Result<(u64, u64)> {
+        require!(!self.config.is_locked(Clock::get()?.unix_timestamp), AmmError::AMMLocked);
+        require!(self.config.is_open(Clock::get()?.unix_timestamp), AmmError::PoolNotOpen);
+        require!(self.config.allows_caller(self.caller_program()?), AmmError::CallerNotAllowed);
+        self.check_delegation(is_x, max_in)?;
+        self.check_sandwich(is_x)?;
+
+        let (reserve_x, reserve_y) = self.config.reserves(self.vault_x.amount, self.vault_y.amount)?;
+
+        // FIXED: Check for zero balance before creating curve
+        require!(reserve_x > 0 && reserve_y > 0, AmmError::InsufficientBalance);
+        require!(self.mint_lp.supply > 0, AmmError::InsufficientBalance);
 
+        // Accumulate the pre-trade price for the time it was in effect
+        self.config.accumulate_prices(reserve_x, reserve_y, Clock::get()?.unix_timestamp);
+        self.record_observation()?;
+
+        Ok((reserve_x, reserve_y))
+    }
+
+    // Books the quoted trade and moves the tokens
+    fn execute(&mut self, args: SwapArgs, res: SwapResult, platform_fee: u64, reserve_x: u64, reserve_y: u64) -> Result<()> {
         require_neq!(res.deposit, 0, AmmError::InvalidAmount);
         require_neq!(res.withdraw, 0, AmmError::InvalidAmount);
+        self.check_band(args.is_x, reserve_x, reserve_y, &res)?;
+        self.check_idle(args.is_x, &res)?;
+
+        let protocol_fee = self.accrue_fees(args.is_x, res.fee)?;
+        self.record_trade(&args, &res, platform_fee)?;
+        self.record_stats(args.is_x, &res, protocol_fee);
 
         let res2 = SwapResult {
             deposit: res.deposit.clone(),
@@ -102,25 +264,308 @@ impl<'info> Swap<'info> {
             fee: res.fee.clone(),
         };
         
+        if platform_fee > 0 {
+            self.transfer_platform_fee(args.is_x, platform_fee)?;
+        }
+
         self.transfer_to_vault(args.clone(), res)?;
         
         self.withdraw_from_vault(args, res2)?;
 
+        self.publish_oracle()?;
+
         Ok(())
     }
 
+    // Portfolio managers and automation services trade for an owner through a delegate approval on
+    // the input account covering the whole input, platform fee included
+    fn check_delegation(&self, is_x: bool, amount: u64) -> Result<()> {
+        if self.user.key() == self.owner.key() {
+            return Ok(());
+        }
+
+        let ata = match is_x {
+            true => &self.user_ata_x,
+            false => &self.user_ata_y,
+        };
+        require!(
+            ata.delegate == COption::Some(self.user.key()) && ata.delegated_amount >= amount,
+            AmmError::InvalidDelegate
+        );
+
+        Ok(())
+    }
+
+    // The top level instruction's program is the one CPI-ing into swap, unless swap is top level itself.
+    // swap_best routing into swap counts as a direct call.
+    fn caller_program(&self) -> Result<Option<Pubkey>> {
+        if get_stack_height() == TRANSACTION_LEVEL_STACK_HEIGHT {
+            return Ok(None);
+        }
+
+        let instructions = self.instructions.to_account_info();
+        let index = load_current_index_checked(&instructions)?;
+        let instruction = load_instruction_at_checked(index as usize, &instructions)?;
+
+        Ok(Some(instruction.program_id).filter(|program_id| *program_id != crate::ID))
+    }
+
+    // With the sandwich guard on, no other top level swap or swap_best in the transaction may trade
+    // this pool the other way. Swaps from other programs' CPIs aren't visible, the CPI policy covers those.
+    fn check_sandwich(&self, is_x: bool) -> Result<()> {
+        if !self.config.sandwich_guard {
+            return Ok(());
+        }
+
+        let instructions = self.instructions.to_account_info();
+        let current = load_current_index_checked(&instructions)? as usize;
+        // The sysvar starts with the number of instructions in the transaction
+        let count = {
+            let data = instructions.try_borrow_data()?;
+            u16::from_le_bytes([data[0], data[1]]) as usize
+        };
+
+        for index in (0..count).filter(|index| *index != current) {
+            let instruction = load_instruction_at_checked(index, &instructions)?;
+            if instruction.program_id != crate::ID || instruction.data.get(8) != Some(&(!is_x as u8)) {
+                continue;
+            }
+
+            let trades_pool = match instruction.data.get(..8) {
+                Some(d) if d == crate::instruction::Swap::DISCRIMINATOR
+                    || d == crate::instruction::SwapExactOut::DISCRIMINATOR => instruction
+                    .accounts
+                    .get(SWAP_CONFIG_ACCOUNT)
+                    .is_some_and(|meta| meta.pubkey == self.config.key()),
+                Some(d) if d == crate::instruction::SwapBest::DISCRIMINATOR => instruction
+                    .accounts
+                    .iter()
+                    .skip(SWAP_BEST_FIXED_ACCOUNTS)
+                    .step_by(SWAP_BEST_POOL_ACCOUNTS)
+                    .any(|meta| meta.pubkey == self.config.key()),
+                _ => false,
+            };
+            require!(!trades_pool, AmmError::SandwichDetected);
+        }
+
+        Ok(())
+    }
+
+    // `swap_amount`, or less if partial fills are on and the full trade would cross the band
+    fn band_fill_amount(&self, is_x: bool, reserve_x: u64, reserve_y: u64, fee: u16, swap_amount: u64, partial_fill: bool) -> u64 {
+        if !partial_fill {
+            return swap_amount;
+        }
+        let Some(limit) = self.config.band_input_limit(is_x, reserve_x, reserve_y) else {
+            return swap_amount;
+        };
+
+        // With the fee on the input only the amount after fees moves the price
+        let max_amount = match self.config.fee_on_output {
+            false => 10_000u128
+                .checked_sub(fee as u128)
+                .and_then(|after_fee| mul_div_wide(limit, 10_000, after_fee, false))
+                .unwrap_or(u64::MAX),
+            true => limit,
+        };
+
+        swap_amount.min(max_amount)
+    }
+
+    // Fees are booked apart from the reserves, so the input after fees goes in and the output before
+    // fees comes out
+    fn check_band(&self, is_x: bool, reserve_x: u64, reserve_y: u64, res: &SwapResult) -> Result<()> {
+        let (amount_in, amount_out) = match self.config.fee_on_output {
+            false => (res.deposit - res.fee, res.withdraw),
+            true => (res.deposit, res.withdraw + res.fee),
+        };
+        let (reserve_x, reserve_y) = match is_x {
+            true => (reserve_x + amount_in, reserve_y - amount_out),
+            false => (reserve_x - amount_out, reserve_y + amount_in),
+        };
+
+        require!(
+            self.config.within_band(is_x, reserve_x, reserve_y, Clock::get()?.unix_timestamp),
+            AmmError::PriceOutOfBand
+        );
+
+        Ok(())
+    }
+
+    // The output leaves the reserves before fees, and only the part still in the vault can be paid
+    fn check_idle(&self, is_x: bool, res: &SwapResult) -> Result<()> {
+        let amount_out = match self.config.fee_on_output {
+            false => res.withdraw,
+            true => res.withdraw + res.fee,
+        };
+        let (out_x, out_y) = match is_x {
+            true => (0, amount_out),
+            false => (amount_out, 0),
+        };
+
+        self.config.check_idle(self.vault_x.amount, self.vault_y.amount, out_x, out_y)
+    }
+
+    fn fee_discount(&self) -> Result<u16> {
+        let Some(account) = self.discount_account.as_ref() else {
+            return Ok(0);
+        };
+
+        require_keys_eq!(account.owner, self.owner.key(), AmmError::InvalidDiscountAccount);
+        require_keys_eq!(account.mint, self.config.discount_mint, AmmError::InvalidDiscountAccount);
+
+        Ok(self.config.fee_discount(account.amount))
+    }
+
+    fn platform_fee_amount(&self, args: &SwapArgs) -> Result<u64> {
+        if args.platform_fee == 0 {
+            return Ok(0);
+        }
+
+        require!(args.platform_fee <= self.global_config.max_platform_fee, AmmError::InvalidFee);
+
+        let input_mint = match args.is_x {
+            true => self.mint_x.key(),
+            false => self.mint_y.key(),
+        };
+        let receiver = self.platform_fee_receiver.as_ref().ok_or(AmmError::InvalidFeeReceiver)?;
+        require_keys_eq!(receiver.mint, input_mint, AmmError::InvalidFeeReceiver);
+
+        let fee = args.amount as u128 * args.platform_fee as u128 / 10_000;
+
+        Ok(fee as u64)
+    }
+
+    fn transfer_platform_fee(&mut self, is_x: bool, amount: u64) -> Result<()> {
+        let receiver = self.platform_fee_receiver.as_ref().ok_or(AmmError::InvalidFeeReceiver)?;
+
+        let (cpi_program, cpi_accounts, mint_decimals) = match is_x {
+            true => (self.token_program_x.to_account_info(), TransferChecked {
+                from: self.user_ata_x.to_account_info(),
+                mint: self.mint_x.to_account_info(),
+                to: receiver.to_account_info(),
+                authority: self.user.to_account_info(),
+            }, self.mint_x.decimals),
+            false => (self.token_program_y.to_account_info(), TransferChecked {
+                from: self.user_ata_y.to_account_info(),
+                mint: self.mint_y.to_account_info(),
+                to: receiver.to_account_info(),
+                authority: self.user.to_account_info(),
+            }, self.mint_y.decimals),
+        };
+
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+
+        transfer_checked(cpi_ctx, amount, mint_decimals)?;
+
+        Ok(())
+    }
+
+    // Keeps the last execution in the pool state so monitors can spot stale pools and outlier prices
+    fn record_trade(&mut self, args: &SwapArgs, res: &SwapResult, platform_fee: u64) -> Result<()> {
+        let (base, quote) = match args.is_x {
+            true => (res.deposit, res.withdraw),
+            false => (res.withdraw, res.deposit),
+        };
+        let price = q64_price(base, quote).ok_or(AmmError::InvalidAmount)?;
+        let slot = Clock::get()?.slot;
+
+        self.config.last_trade_price = price;
+        self.config.last_trade_is_x = args.is_x;
+        self.config.last_trade_slot = slot;
+
+        emit!(SwapExecuted {
+            config: self.config.key(),
+            user: self.user.key(),
+            owner: self.owner.key(),
+            is_x: args.is_x,
+            amount_in: res.deposit,
+            amount_out: res.withdraw,
+            fee: res.fee,
+            platform_fee,
+            price,
+            slot,
+        });
+
+        Ok(())
+    }
+
+    fn record_observation(&mut self) -> Result<()> {
+        let mut observations = self.observations.load_mut()?;
+        observations.write(
+            self.config.last_observation_ts,
+            self.config.price_x_cumulative,
+            self.config.price_y_cumulative,
+        );
+
+        Ok(())
+    }
+
+    // Publishes the post-trade spot price
+    fn publish_oracle(&mut self) -> Result<()> {
+        self.vault_x.reload()?;
+        self.vault_y.reload()?;
+
+        let (reserve_x, reserve_y) = self.config.reserves(self.vault_x.amount, self.vault_y.amount)?;
+        let observations = self.observations.load()?;
+        let mut oracle = self.oracle.load_mut()?;
+        oracle.update(&self.config, &observations, reserve_x, reserve_y, Clock::get()?.slot);
+
+        Ok(())
+    }
+
+    // Fees stay in the vault of whichever token they were charged in, but are booked apart from the
+    // reserves: the protocol's and insurance fund's cuts in their own balances, the rest as LP fees.
+    // Returns the protocol's cut.
+    fn accrue_fees(&mut self, is_x: bool, fee: u64) -> Result<u64> {
+        let protocol_fee = (fee as u128 * self.global_config.protocol_fee as u128 / 10_000) as u64;
+        let insurance_fee = (fee as u128 * self.global_config.insurance_fee as u128 / 10_000) as u64;
+        let lp_fee = fee
+            .checked_sub(protocol_fee)
+            .and_then(|f| f.checked_sub(insurance_fee))
+            .ok_or(AmmError::InvalidFee)?;
+
+        let config: &mut Config = &mut self.config;
+
+        // Input side unless the pool charges the fee on the output token. With epochs enabled the
+        // LP fees in token Y go to the running epoch's stakers instead of compounding.
+        let (protocol_fees, insurance_fees, lp_fees) = match (is_x != config.fee_on_output, config.epoch_duration > 0) {
+            (true, _) => (&mut config.protocol_fees_x, &mut config.insurance_x, &mut config.lp_fees_x),
+            (false, false) => (&mut config.protocol_fees_y, &mut config.insurance_y, &mut config.lp_fees_y),
+            (false, true) => (&mut config.protocol_fees_y, &mut config.insurance_y, &mut config.epoch_fees_y),
+        };
+
+        *protocol_fees = protocol_fees.checked_add(protocol_fee).ok_or(AmmError::InvalidAmount)?;
+        *insurance_fees = insurance_fees.checked_add(insurance_fee).ok_or(AmmError::InvalidAmount)?;
+        *lp_fees = lp_fees.checked_add(lp_fee).ok_or(AmmError::InvalidAmount)?;
+
+        Ok(protocol_fee)
+    }
+
+    fn record_stats(&mut self, is_x: bool, res: &SwapResult, protocol_fee: u64) {
+        let (mint_in, mint_out) = match is_x {
+            true => (self.mint_x.key(), self.mint_y.key()),
+            false => (self.mint_y.key(), self.mint_x.key()),
+        };
+        let fee_mint = match self.config.fee_on_output {
+            true => mint_out,
+            false => mint_in,
+        };
+
+        self.protocol_stats.record_swap(mint_in, res.deposit, fee_mint, protocol_fee);
+    }
+
     fn transfer_to_vault(&mut self, args: SwapArgs, res: SwapResult) -> Result<()> {
-        let cpi_program = self.token_program.to_account_info();
 
         // FIXED: Correct token account assignment
-        let (cpi_accounts, mint_decimals) = match args.is_x {
-            true => ( TransferChecked {
+        let (cpi_program, cpi_accounts, mint_decimals) = match args.is_x {
+            true => (self.token_program_x.to_account_info(), TransferChecked {
                 from: self.user_ata_x.to_account_info(),
                 mint: self.mint_x.to_account_info(),
                 to: self.vault_x.to_account_info(),
                 authority: self.user.to_account_info(),
             }, self.mint_x.decimals),
-            false => ( TransferChecked {
+            false => (self.token_program_y.to_account_info(), TransferChecked {
                 from: self.user_ata_y.to_account_info(), // FIXED: was user_ata_x
                 mint: self.mint_y.to_account_info(),     // FIXED: was mint_x
                 to: self.vault_y.to_account_info(),      // FIXED: was vault_x
@@ -136,17 +581,15 @@ impl<'info> Swap<'info> {
     }
 
     fn withdraw_from_vault(&mut self, args: SwapArgs, res: SwapResult) -> Result<()> {
-        let cpi_program = self.token_program.to_account_info();
-
-        let (cpi_accounts, mint_decimals) = match args.is_x {
-            true => (TransferChecked {
+        let (cpi_program, cpi_accounts, mint_decimals) = match args.is_x {
+            true => (self.token_program_y.to_account_info(), TransferChecked {
                 from: self.vault_y.to_account_info(),
                 mint: self.mint_y.to_account_info(),
                 to: self.user_ata_y.to_account_info(),
                 authority: self.config.to_account_info(),
             }, self.mint_y.decimals),
 
-            false => (TransferChecked {
+            false => (self.token_program_x.to_account_info(), TransferChecked {
                 from: self.vault_x.to_account_info(),
                 mint: self.mint_x.to_account_info(),
                 to: self.user_ata_x.to_account_info(),
@@ -0,0 +1,145 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::instructions as instructions_sysvar;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::Token;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::program::Amm;
+use crate::state::{Config, GlobalConfig, ProtocolStats};
+use crate::errors::AmmError;
+
+// Accounts each candidate pool takes in remaining_accounts, in this order
+pub const SWAP_BEST_POOL_ACCOUNTS: usize = 6; // config, observations, oracle, mint_lp, vault_x, vault_y
+// Accounts of SwapBest itself, ahead of the pools
+pub const SWAP_BEST_FIXED_ACCOUNTS: usize = 15;
+
+// Swaps against whichever of several pools on the same pair quotes the most output. Everything
+// shared goes here; the per-pool accounts come in remaining_accounts and the chosen pool's are
+// fully validated by the swap it's routed to.
+#[derive(Accounts)]
+pub struct SwapBest<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    /// CHECK: checked by swap, the owner of the token accounts
+    pub owner: UncheckedAccount<'info>,
+    #[account(
+        seeds = [b"global"],
+        bump = global_config.bump,
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    #[account(
+        mut,
+        seeds = [b"stats"],
+        bump = protocol_stats.bump,
+    )]
+    pub protocol_stats: Account<'info, ProtocolStats>,
+    pub mint_x: InterfaceAccount<'info, Mint>,
+    pub mint_y: InterfaceAccount<'info, Mint>,
+    /// CHECK: checked by swap, the owner's token X account, created there if needed
+    #[account(mut)]
+    pub user_ata_x: UncheckedAccount<'info>,
+    /// CHECK: checked by swap, the owner's token Y account, created there if needed
+    #[account(mut)]
+    pub user_ata_y: UncheckedAccount<'info>,
+    /// CHECK: the instructions sysvar, forwarded to swap
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub token_program_x: Interface<'info, TokenInterface>,
+    pub token_program_y: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub amm_program: Program<'info, Amm>,
+}
+
+impl<'info> SwapBest<'info> {
+    pub fn swap_best(&mut self, pools: &[AccountInfo<'info>], is_x: bool, amount: u64, min: u64) -> Result<()> {
+        require!(
+            !pools.is_empty() && pools.len() % SWAP_BEST_POOL_ACCOUNTS == 0,
+            AmmError::InvalidConfig
+        );
+
+        let mut best: Option<(&[AccountInfo<'info>], u64)> = None;
+        for pool in pools.chunks(SWAP_BEST_POOL_ACCOUNTS) {
+            let Some(amount_out) = self.quote(pool, is_x, amount)? else {
+                continue;
+            };
+            if best.map(|(_, best_out)| best_out) < Some(amount_out) {
+                best = Some((pool, amount_out));
+            }
+        }
+
+        let (pool, _) = best.ok_or(AmmError::InsufficientBalance)?;
+        self.route(pool, is_x, amount, min)
+    }
+
+    // Output the pool would pay at its current fee, None if it can't take the trade. Discounts,
+    // platform fees and price bands aren't considered, swap applies them to the chosen pool.
+    fn quote(&self, pool: &[AccountInfo<'info>], is_x: bool, amount: u64) -> Result<Option<u64>> {
+        let config = Account::<Config>::try_from(&pool[0])?;
+        require_keys_eq!(config.mint_x, self.mint_x.key(), AmmError::InvalidConfig);
+        require_keys_eq!(config.mint_y, self.mint_y.key(), AmmError::InvalidConfig);
+
+        let vault_x = InterfaceAccount::<TokenAccount>::try_from(&pool[4])?;
+        let vault_y = InterfaceAccount::<TokenAccount>::try_from(&pool[5])?;
+        let expected_x = Pubkey::create_program_address(
+            &[b"vault_x", config.key().as_ref(), &[config.vault_x_bump]],
+            &crate::ID,
+        ).map_err(|_| AmmError::InvalidConfig)?;
+        let expected_y = Pubkey::create_program_address(
+            &[b"vault_y", config.key().as_ref(), &[config.vault_y_bump]],
+            &crate::ID,
+        ).map_err(|_| AmmError::InvalidConfig)?;
+        require_keys_eq!(vault_x.key(), expected_x, AmmError::InvalidConfig);
+        require_keys_eq!(vault_y.key(), expected_y, AmmError::InvalidConfig);
+
+        let clock = Clock::get()?;
+        if config.is_locked(clock.unix_timestamp) || !config.is_open(clock.unix_timestamp) || amount < config.min_swap_amount {
+            return Ok(None);
+        }
+
+        let (reserve_x, reserve_y) = config.reserves(vault_x.amount, vault_y.amount)?;
+        if reserve_x == 0 || reserve_y == 0 {
+            return Ok(None);
+        }
+
+        let fee = config.swap_fee(is_x, clock.slot);
+        let quote = config.swap_quote(is_x, reserve_x, reserve_y, amount, fee, clock.unix_timestamp);
+
+        Ok(quote.map(|quote| quote.amount_out))
+    }
+
+    fn route(&self, pool: &[AccountInfo<'info>], is_x: bool, amount: u64, min: u64) -> Result<()> {
+        let cpi_program = self.amm_program.to_account_info();
+
+        let cpi_accounts = crate::cpi::accounts::Swap {
+            user: self.user.to_account_info(),
+            owner: self.owner.to_account_info(),
+            global_config: self.global_config.to_account_info(),
+            protocol_stats: self.protocol_stats.to_account_info(),
+            config: pool[0].clone(),
+            observations: pool[1].clone(),
+            oracle: pool[2].clone(),
+            mint_lp: pool[3].clone(),
+            mint_x: self.mint_x.to_account_info(),
+            mint_y: self.mint_y.to_account_info(),
+            vault_x: pool[4].clone(),
+            vault_y: pool[5].clone(),
+            user_ata_x: self.user_ata_x.to_account_info(),
+            user_ata_y: self.user_ata_y.to_account_info(),
+            platform_fee_receiver: None,
+            discount_account: None,
+            instructions: self.instructions.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+            token_program_x: self.token_program_x.to_account_info(),
+            token_program_y: self.token_program_y.to_account_info(),
+            system_program: self.system_program.to_account_info(),
+            associated_token_program: self.associated_token_program.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+
+        crate::cpi::swap(cpi_ctx, is_x, amount, min, 0)
+    }
+}
@@ -0,0 +1,209 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+
+use crate::state::{Config, CpiPolicy, DiscountTier, MAX_CPI_PROGRAMS, MAX_DISCOUNT_TIERS};
+use crate::errors::AmmError;
+use crate::constants::{MAX_DEPLOYED_BPS, MIN_LBP_WEIGHT, STRATEGY_TIMELOCK_SECS};
+
+// Pool settings that only the pool authority can change
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    pub authority: Signer<'info>,
+    pub mint_x: InterfaceAccount<'info, Mint>,
+    pub mint_y: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        has_one = mint_x,
+        has_one = mint_y,
+        constraint = config.authority == Some(authority.key()) @ AmmError::Unauthorized,
+        constraint = !config.immutable @ AmmError::ConfigImmutable,
+        seeds = [
+            b"config",
+            mint_x.key().to_bytes().as_ref(),
+            mint_y.key().to_bytes().as_ref(),
+            config.seed.to_le_bytes().as_ref()
+        ],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+impl<'info> UpdateConfig<'info> {
+    // Pauses swaps, deposits and withdrawals. With a nonzero `auto_unlock_ts` the pause ends by itself
+    // at that time, even if the authority's key is lost.
+    pub fn lock(&mut self, auto_unlock_ts: i64) -> Result<()> {
+        require!(auto_unlock_ts == 0 || auto_unlock_ts > Clock::get()?.unix_timestamp, AmmError::InvalidConfig);
+
+        self.config.locked = true;
+        self.config.auto_unlock_ts = auto_unlock_ts;
+
+        Ok(())
+    }
+
+    pub fn unlock(&mut self) -> Result<()> {
+        self.config.locked = false;
+        self.config.auto_unlock_ts = 0;
+
+        Ok(())
+    }
+
+    // Starts a fee that decays linearly from `launch_fee` to the base fee over `slots` slots,
+    // charged on swaps selling X when `on_x` is set and on swaps selling Y otherwise
+    pub fn set_launch_fee(&mut self, launch_fee: u16, slots: u64, on_x: bool) -> Result<()> {
+        require!(launch_fee <= 10_000 && launch_fee >= self.config.fee, AmmError::InvalidFee);
+
+        self.config.launch_fee = launch_fee;
+        self.config.launch_fee_start_slot = Clock::get()?.slot;
+        self.config.launch_fee_slots = slots;
+        self.config.launch_fee_on_x = on_x;
+
+        Ok(())
+    }
+
+    pub fn set_cpi_policy(&mut self, policy: CpiPolicy, programs: Vec<Pubkey>) -> Result<()> {
+        require!(programs.len() <= MAX_CPI_PROGRAMS, AmmError::AllowlistFull);
+
+        self.config.cpi_policy = policy;
+        self.config.cpi_programs = programs;
+
+        Ok(())
+    }
+
+    // Rewards holders of `mint` with swap fee discounts, an empty tier list turns discounts off
+    pub fn set_discount_tiers(&mut self, mint: Pubkey, tiers: Vec<DiscountTier>) -> Result<()> {
+        require!(tiers.len() <= MAX_DISCOUNT_TIERS, AmmError::InvalidConfig);
+        require!(tiers.iter().all(|tier| tier.discount <= 10_000), AmmError::InvalidFee);
+
+        self.config.discount_mint = mint;
+        self.config.discount_tiers = tiers;
+
+        Ok(())
+    }
+
+    // Switches LP fees in token Y from compounding to per-epoch distribution to stakers
+    pub fn set_epoch_duration(&mut self, epoch_duration: i64) -> Result<()> {
+        // Staking moves LP tokens through the transfer hook, which can't reenter this program
+        require!(!self.config.track_lp_holders, AmmError::LpHoldersTracked);
+
+        self.config.set_epoch_duration(epoch_duration, Clock::get()?.unix_timestamp)
+    }
+
+    // Caps the pool's size in LP supply terms for guarded launches, raised as confidence grows
+    pub fn set_max_lp_supply(&mut self, max_lp_supply: u64) -> Result<()> {
+        self.config.max_lp_supply = max_lp_supply;
+
+        Ok(())
+    }
+
+    // Limits the LP tokens any one wallet can mint until `end_ts`, so no single depositor owns a
+    // community launch. 0 turns the cap off.
+    pub fn set_launch_deposit_cap(&mut self, cap: u64, end_ts: i64) -> Result<()> {
+        self.config.launch_deposit_cap = cap;
+        self.config.launch_deposit_end_ts = end_ts;
+
+        Ok(())
+    }
+
+    // Makes LPs wait `secs` between request_withdraw and withdrawing, against flash-LPing around rewards
+    pub fn set_withdraw_cooldown(&mut self, secs: i64) -> Result<()> {
+        require!(secs >= 0, AmmError::InvalidConfig);
+
+        self.config.withdraw_cooldown_secs = secs;
+
+        Ok(())
+    }
+
+    pub fn set_dust_thresholds(&mut self, min_swap_amount: u64, min_withdraw_lp: u64) -> Result<()> {
+        self.config.min_swap_amount = min_swap_amount;
+        self.config.min_withdraw_lp = min_withdraw_lp;
+
+        Ok(())
+    }
+
+    // Q64.64 bounds on the price of X in Y, 0 leaves a side unbounded. With `partial_fill` swaps that
+    // would cross a bound are filled up to it rather than rejected.
+    pub fn set_price_band(&mut self, min_price: u128, max_price: u128, partial_fill: bool) -> Result<()> {
+        require!(max_price == 0 || min_price <= max_price, AmmError::InvalidConfig);
+
+        self.config.min_price = min_price;
+        self.config.max_price = max_price;
+        self.config.band_partial_fill = partial_fill;
+
+        Ok(())
+    }
+
+    // Turns the pool into a liquidity bootstrapping pool whose weight of X moves linearly from
+    // `start_weight_x` to `end_weight_x` basis points between the timestamps. Only before the first
+    // deposit, since changing weights moves the price.
+    pub fn set_lbp_schedule(&mut self, start_weight_x: u16, end_weight_x: u16, start_ts: i64, end_ts: i64) -> Result<()> {
+        require!(self.config.total_shares == 0, AmmError::InvalidConfig);
+        require!(start_ts > 0 && end_ts > start_ts, AmmError::InvalidLbpSchedule);
+        for weight in [start_weight_x, end_weight_x] {
+            require!((MIN_LBP_WEIGHT..=10_000 - MIN_LBP_WEIGHT).contains(&weight), AmmError::InvalidLbpSchedule);
+        }
+
+        self.config.lbp_start_weight_x = start_weight_x;
+        self.config.lbp_end_weight_x = end_weight_x;
+        self.config.lbp_start_ts = start_ts;
+        self.config.lbp_end_ts = end_ts;
+
+        Ok(())
+    }
+
+    // Designates the pool LPs can move to with migrate_liquidity, or None to stop migrations
+    pub fn set_successor(&mut self, successor: Option<Pubkey>) -> Result<()> {
+        require!(successor != Some(self.config.key()), AmmError::InvalidSuccessor);
+
+        self.config.successor = successor;
+
+        Ok(())
+    }
+
+    // Opt-in MEV deterrent for retail pools, see Swap::check_sandwich
+    pub fn set_sandwich_guard(&mut self, enabled: bool) -> Result<()> {
+        self.config.sandwich_guard = enabled;
+
+        Ok(())
+    }
+
+    // Designates the account whose token accounts deploy_idle lends reserves to. The strategy can
+    // only be replaced once it returned everything. A new strategy or a higher limit only applies
+    // after STRATEGY_TIMELOCK_SECS, giving LPs time to exit before reserves can go to an account the
+    // authority picked; turning deploying off or lowering the limit applies right away.
+    pub fn set_strategy(&mut self, strategy: Option<Pubkey>, max_deployed_bps: u16) -> Result<()> {
+        require!(max_deployed_bps <= MAX_DEPLOYED_BPS, AmmError::InvalidConfig);
+        require!(
+            strategy == self.config.strategy || (self.config.deployed_x == 0 && self.config.deployed_y == 0),
+            AmmError::StrategyHoldsReserves
+        );
+
+        if strategy.is_some() && (strategy != self.config.strategy || max_deployed_bps > self.config.max_deployed_bps) {
+            self.config.strategy_active_ts = Clock::get()?.unix_timestamp + STRATEGY_TIMELOCK_SECS;
+        }
+
+        self.config.strategy = strategy;
+        self.config.max_deployed_bps = max_deployed_bps;
+
+        Ok(())
+    }
+
+    // Recurring trading windows counted from open_time, e.g. a 86_400 period with 28_800 open
+    // seconds trades eight hours a day. A period of 0 keeps the pool open around the clock.
+    pub fn set_trading_windows(&mut self, period: i64, open_secs: i64) -> Result<()> {
+        require!(period >= 0, AmmError::InvalidConfig);
+        require!(period == 0 || (open_secs > 0 && open_secs <= period), AmmError::InvalidConfig);
+
+        self.config.window_period = period;
+        self.config.window_open_secs = match period {
+            0 => 0,
+            _ => open_secs,
+        };
+
+        Ok(())
+    }
+
+    // One way: every setter here is rejected from now on, so LPs can rely on the current settings
+    pub fn renounce_authority(&mut self) -> Result<()> {
+        self.config.renounce(Clock::get()?.unix_timestamp)
+    }
+}
@@ -0,0 +1,113 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+use anchor_spl::token_interface::{Mint, TokenAccount};
+
+use crate::state::{Config, Observations, PriceOracle};
+use crate::constants::{OBSERVATION_CRANK_REWARD, OBSERVATION_IDLE_SECS};
+
+#[derive(Accounts)]
+pub struct UpdateObservation<'info> {
+    #[account(mut)]
+    pub caller: Signer<'info>,
+    pub mint_x: InterfaceAccount<'info, Mint>,
+    pub mint_y: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [
+            b"config",
+            mint_x.key().to_bytes().as_ref(),
+            mint_y.key().to_bytes().as_ref(),
+            config.seed.to_le_bytes().as_ref()
+        ],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        seeds = [b"observations", config.key().as_ref()],
+        bump = config.observations_bump,
+    )]
+    pub observations: AccountLoader<'info, Observations>,
+    #[account(
+        mut,
+        seeds = [b"oracle", config.key().as_ref()],
+        bump = config.oracle_bump,
+    )]
+    pub oracle: AccountLoader<'info, PriceOracle>,
+    #[account(
+        seeds = [b"vault_x", config.key().as_ref()],
+        bump = config.vault_x_bump,
+    )]
+    pub vault_x: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        seeds = [b"vault_y", config.key().as_ref()],
+        bump = config.vault_y_bump,
+    )]
+    pub vault_y: InterfaceAccount<'info, TokenAccount>,
+    // Lamports sent to this address by anyone fund the crank rewards of the pool
+    #[account(
+        mut,
+        seeds = [b"crank", config.key().as_ref()],
+        bump,
+    )]
+    pub crank_vault: SystemAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> UpdateObservation<'info> {
+    pub fn update_observation(&mut self, bumps: &UpdateObservationBumps) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let idle = now.saturating_sub(self.config.last_observation_ts) >= OBSERVATION_IDLE_SECS;
+
+        let (reserve_x, reserve_y) = self.config.reserves(self.vault_x.amount, self.vault_y.amount)?;
+        self.config.accumulate_prices(reserve_x, reserve_y, now);
+
+        let mut observations = self.observations.load_mut()?;
+        observations.write(
+            self.config.last_observation_ts,
+            self.config.price_x_cumulative,
+            self.config.price_y_cumulative,
+        );
+
+        let mut oracle = self.oracle.load_mut()?;
+        oracle.update(&self.config, &observations, reserve_x, reserve_y, Clock::get()?.slot);
+        drop(oracle);
+        drop(observations);
+
+        if idle {
+            self.pay_reward(bumps.crank_vault)?;
+        }
+
+        Ok(())
+    }
+
+    // An underfunded crank vault doesn't block the update, the caller just isn't paid
+    fn pay_reward(&mut self, crank_bump: u8) -> Result<()> {
+        let rent_exempt = Rent::get()?.minimum_balance(0);
+        let available = self.crank_vault.lamports().saturating_sub(rent_exempt);
+
+        if available < OBSERVATION_CRANK_REWARD {
+            return Ok(());
+        }
+
+        let cpi_program = self.system_program.to_account_info();
+
+        let cpi_accounts = Transfer {
+            from: self.crank_vault.to_account_info(),
+            to: self.caller.to_account_info(),
+        };
+
+        let config = self.config.key();
+        let seeds = [b"crank", config.as_ref(), &[crank_bump]];
+
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+        transfer(cpi_ctx, OBSERVATION_CRANK_REWARD)?;
+
+        Ok(())
+    }
+}
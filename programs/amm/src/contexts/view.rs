@@ -0,0 +1,106 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount};
+
+use crate::state::Config;
+use crate::constants::MINIMUM_LIQUIDITY;
+use crate::errors::AmmError;
+use crate::math::q64_price;
+
+// Redemption value of one whole LP token (10^6 base units)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct VirtualPrice {
+    pub x: u64,
+    pub y: u64,
+}
+
+// Everything a client needs about a pool in one simulate call
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct PoolInfo {
+    pub reserve_x: u64,
+    pub reserve_y: u64,
+    pub lp_supply: u64,
+    pub total_shares: u128, // internal shares behind lp_supply
+    pub fee: u16,
+    pub fee_on_output: bool,
+    pub locked: bool,
+    pub spot_price: u128, // Q64.64 price of X in Y, 0 while the pool is empty
+    pub protocol_fees_x: u64,
+    pub protocol_fees_y: u64,
+    pub lp_fees_x: u64,
+    pub lp_fees_y: u64,
+}
+
+// Read-only pool accounts for instructions that only report through return data
+#[derive(Accounts)]
+pub struct PoolView<'info> {
+    pub mint_x: InterfaceAccount<'info, Mint>,
+    pub mint_y: InterfaceAccount<'info, Mint>,
+    #[account(
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [
+            b"config",
+            mint_x.key().to_bytes().as_ref(),
+            mint_y.key().to_bytes().as_ref(),
+            config.seed.to_le_bytes().as_ref()
+        ],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        seeds = [b"lp", config.key().as_ref()],
+        bump = config.lp_bump,
+    )]
+    pub mint_lp: InterfaceAccount<'info, Mint>,
+    #[account(
+        seeds = [b"vault_x", config.key().as_ref()],
+        bump = config.vault_x_bump,
+    )]
+    pub vault_x: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        seeds = [b"vault_y", config.key().as_ref()],
+        bump = config.vault_y_bump,
+    )]
+    pub vault_y: InterfaceAccount<'info, TokenAccount>,
+}
+
+impl<'info> PoolView<'info> {
+    pub fn virtual_price(&self) -> Result<VirtualPrice> {
+        require!(self.mint_lp.supply > 0, AmmError::InsufficientBalance);
+
+        let (reserve_x, reserve_y) = self.config.reserves(self.vault_x.amount, self.vault_y.amount)?;
+        let one_lp = 10u128.pow(self.mint_lp.decimals as u32);
+
+        // LPs redeem their share of the earned fees along with the reserves, the locked
+        // MINIMUM_LIQUIDITY owns its part of both
+        let supply = self.mint_lp.supply as u128 + MINIMUM_LIQUIDITY as u128;
+        let x = (reserve_x as u128 + self.config.lp_fees_x as u128) * one_lp / supply;
+        let y = (reserve_y as u128 + self.config.lp_fees_y as u128) * one_lp / supply;
+
+        Ok(VirtualPrice {
+            x: u64::try_from(x).map_err(|_| AmmError::InvalidAmount)?,
+            y: u64::try_from(y).map_err(|_| AmmError::InvalidAmount)?,
+        })
+    }
+
+    pub fn pool_info(&self) -> Result<PoolInfo> {
+        let (reserve_x, reserve_y) = self.config.reserves(self.vault_x.amount, self.vault_y.amount)?;
+        let now = Clock::get()?.unix_timestamp;
+        let (spot_x, spot_y) = self.config.price_reserves(reserve_x, reserve_y, now);
+
+        Ok(PoolInfo {
+            reserve_x,
+            reserve_y,
+            lp_supply: self.mint_lp.supply,
+            total_shares: self.config.total_shares,
+            fee: self.config.fee,
+            fee_on_output: self.config.fee_on_output,
+            locked: self.config.is_locked(now),
+            spot_price: q64_price(spot_x, spot_y).unwrap_or(0),
+            protocol_fees_x: self.config.protocol_fees_x,
+            protocol_fees_y: self.config.protocol_fees_y,
+            lp_fees_x: self.config.lp_fees_x,
+            lp_fees_y: self.config.lp_fees_y,
+        })
+    }
+}
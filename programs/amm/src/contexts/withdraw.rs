@@ -1,19 +1,25 @@
 use anchor_lang::prelude::*;
 use anchor_spl::associated_token::AssociatedToken;
-use anchor_spl::token::{burn, transfer_checked, Burn, TransferChecked, Token, Mint, TokenAccount};
+use anchor_spl::token_interface::{
+    burn, close_account, freeze_account, thaw_account, transfer_checked, Burn, CloseAccount, FreezeAccount,
+    ThawAccount, TransferChecked, Mint, TokenAccount, TokenInterface,
+};
 
-use constant_product_curve::ConstantProduct;
-
-use crate::state::Config;
+use crate::state::{Config, LpHolders, WithdrawRequest};
 use crate::errors::AmmError;
+use crate::events::log_slippage;
+use crate::quote::withdraw_amounts;
 
 #[derive(Accounts)]
 pub struct Withdraw<'info> {
     #[account(mut)]
     pub lp_provider: Signer<'info>,
-    pub mint_x: Account<'info, Mint>,
-    pub mint_y: Account<'info, Mint>, 
+    #[account(mint::token_program = token_program_x)]
+    pub mint_x: InterfaceAccount<'info, Mint>,
+    #[account(mint::token_program = token_program_y)]
+    pub mint_y: InterfaceAccount<'info, Mint>, 
     #[account(
+        mut,
         has_one = mint_x,
         has_one = mint_y,
         seeds = [
@@ -32,40 +38,59 @@ pub struct Withdraw<'info> {
         mint::decimals = 6,
         mint::authority = config
     )]
-    pub mint_lp: Account<'info, Mint>,
+    pub mint_lp: InterfaceAccount<'info, Mint>,
     #[account(
         mut,
-        associated_token::mint = mint_x,
-        associated_token::authority = config,
+        seeds = [b"vault_x", config.key().as_ref()],
+        bump = config.vault_x_bump,
     )]
-    pub vault_x: Account<'info, TokenAccount>,
+    pub vault_x: InterfaceAccount<'info, TokenAccount>,
     #[account(
         mut,
-        associated_token::mint = mint_y,
-        associated_token::authority = config,
+        seeds = [b"vault_y", config.key().as_ref()],
+        bump = config.vault_y_bump,
     )]
-    pub vault_y: Account<'info, TokenAccount>,
+    pub vault_y: InterfaceAccount<'info, TokenAccount>,
     #[account(
         mut,
         associated_token::authority = lp_provider,
         associated_token::mint = mint_x,
+        associated_token::token_program = token_program_x,
     )]
-    pub lp_provider_ata_x: Account<'info, TokenAccount>,
+    pub lp_provider_ata_x: InterfaceAccount<'info, TokenAccount>,
     #[account(
         mut,
         associated_token::authority = lp_provider,
         associated_token::mint = mint_y,
+        associated_token::token_program = token_program_y,
     )]
-    pub lp_provider_ata_y: Account<'info, TokenAccount>,
+    pub lp_provider_ata_y: InterfaceAccount<'info, TokenAccount>,
     #[account(
         init_if_needed,
         payer = lp_provider,
         associated_token::authority = lp_provider,
         associated_token::mint = mint_lp,
     )]
-    pub lp_provider_ata_lp: Account<'info, TokenAccount>,
+    pub lp_provider_ata_lp: InterfaceAccount<'info, TokenAccount>,
+    // Required when the pool has a withdrawal cooldown
+    #[account(
+        mut,
+        seeds = [b"withdraw_request", config.key().as_ref(), lp_provider.key().as_ref()],
+        bump = withdraw_request.bump,
+    )]
+    pub withdraw_request: Option<Account<'info, WithdrawRequest>>,
+    // Required when the pool tracks LP holders
+    #[account(
+        mut,
+        seeds = [b"lp_holders", mint_lp.key().as_ref()],
+        bump = lp_holders.load()?.bump,
+    )]
+    pub lp_holders: Option<AccountLoader<'info, LpHolders>>,
 
-    pub token_program: Program<'info, Token>,
+    // Program of the LP mint
+    pub token_program: Interface<'info, TokenInterface>,
+    pub token_program_x: Interface<'info, TokenInterface>,
+    pub token_program_y: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
     pub associated_token_program: Program<'info, AssociatedToken>,
 }
@@ -73,38 +98,82 @@ pub struct Withdraw<'info> {
 impl<'info> Withdraw<'info> {
     pub fn withdraw(&mut self, lp_amount: u64, min_x: u64, min_y: u64) -> Result<()> {
         require!(lp_amount > 0, AmmError::InvalidAmount);
-        require!(!self.config.locked, AmmError::AMMLocked);
+        require!(
+            !self.config.is_dust_withdrawal(lp_amount, self.lp_provider_ata_lp.amount),
+            AmmError::DustAmount
+        );
+        require!(!self.config.is_locked(Clock::get()?.unix_timestamp), AmmError::AMMLocked);
+        self.consume_withdraw_request(lp_amount)?;
+
+        let (reserve_x, reserve_y) = self.config.reserves(self.vault_x.amount, self.vault_y.amount)?;
 
-        let xy_amount = ConstantProduct::xy_withdraw_amounts_from_l(
-            self.vault_x.amount, 
-            self.vault_y.amount, 
-            self.mint_lp.supply, 
-            lp_amount, 
-            6,
-        ).map_err(|_| AmmError::InvalidAmount)?; // FIXED: Handle error properly
+        // Exiting LPs take their share of the fees earned on top of the reserves
+        let quote = withdraw_amounts(&self.config.pool_state(reserve_x, reserve_y, self.mint_lp.supply), lp_amount)
+            .ok_or(AmmError::InsufficientBalance)?;
+        let (x, y) = (quote.amount_x, quote.amount_y);
 
-        require!(min_x <= xy_amount.x, AmmError::InsufficientTokenX);
-        require!(min_y <= xy_amount.y, AmmError::InsufficientTokenY);
+        if x < min_x {
+            log_slippage(self.config.key(), self.lp_provider.key(), self.mint_x.key(), x, min_x);
+        }
+        if y < min_y {
+            log_slippage(self.config.key(), self.lp_provider.key(), self.mint_y.key(), y, min_y);
+        }
+        require!(min_x <= x, AmmError::InsufficientTokenX);
+        require!(min_y <= y, AmmError::InsufficientTokenY);
+        self.config.check_idle(self.vault_x.amount, self.vault_y.amount, x - quote.fee_x, y - quote.fee_y)?;
 
-        self.withdraw_tokens(true, xy_amount.x)?;
-        self.withdraw_tokens(false, xy_amount.y)?;
+        self.config.lp_fees_x -= quote.fee_x;
+        self.config.lp_fees_y -= quote.fee_y;
+        self.config.total_shares -= quote.shares;
+
+        self.withdraw_tokens(true, x)?;
+        self.withdraw_tokens(false, y)?;
+        let remaining_lp = self.lp_provider_ata_lp.amount.checked_sub(lp_amount).ok_or(AmmError::InsufficientBalance)?;
+
+        if self.config.soulbound && self.lp_provider_ata_lp.is_frozen() {
+            self.set_lp_frozen(false)?;
+        }
         self.burn_lp_tokens(lp_amount)?;
+        if self.config.track_lp_holders {
+            let lp_holders = self.lp_holders.as_ref().ok_or(AmmError::MissingLpHolders)?;
+            lp_holders.load_mut()?.debit(self.lp_provider.key(), lp_amount)?;
+        }
+        // A fully exited account stays thawed so it can be closed
+        if self.config.soulbound && remaining_lp > 0 {
+            self.set_lp_frozen(true)?;
+        }
 
         Ok(())
     }
 
-    fn withdraw_tokens(&mut self, is_x: bool, amount: u64) -> Result<()> {
-        let cpi_program = self.token_program.to_account_info();
+    // Full exit: burns the whole LP balance and closes the LP token account, refunding its rent
+    pub fn withdraw_all(&mut self, min_x: u64, min_y: u64) -> Result<()> {
+        self.withdraw(self.lp_provider_ata_lp.amount, min_x, min_y)?;
+        self.close_lp_account()?;
+
+        Ok(())
+    }
 
-        let (cpi_accounts, mint_decimals) = match is_x {
-            true => (TransferChecked {
+    fn consume_withdraw_request(&mut self, lp_amount: u64) -> Result<()> {
+        if self.config.withdraw_cooldown_secs == 0 {
+            return Ok(());
+        }
+
+        let request = self.withdraw_request.as_mut().ok_or(AmmError::WithdrawNotRequested)?;
+
+        request.consume(lp_amount, self.lp_provider_ata_lp.amount, Clock::get()?.unix_timestamp)
+    }
+
+    fn withdraw_tokens(&mut self, is_x: bool, amount: u64) -> Result<()> {
+        let (cpi_program, cpi_accounts, mint_decimals) = match is_x {
+            true => (self.token_program_x.to_account_info(), TransferChecked {
                 from: self.vault_x.to_account_info(),
                 mint: self.mint_x.to_account_info(),
                 to: self.lp_provider_ata_x.to_account_info(),
                 authority: self.config.to_account_info(),
             }, self.mint_x.decimals),
 
-            false => (TransferChecked {
+            false => (self.token_program_y.to_account_info(), TransferChecked {
                 from: self.vault_y.to_account_info(),
                 mint: self.mint_y.to_account_info(),
                 to: self.lp_provider_ata_y.to_account_info(),
@@ -149,4 +218,60 @@ impl<'info> Withdraw<'info> {
 
         Ok(())
     }
-}
\ No newline at end of file
+
+    fn close_lp_account(&mut self) -> Result<()> {
+        let cpi_program = self.token_program.to_account_info();
+
+        let cpi_accounts = CloseAccount {
+            account: self.lp_provider_ata_lp.to_account_info(),
+            destination: self.lp_provider.to_account_info(),
+            authority: self.lp_provider.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+
+        close_account(cpi_ctx)?;
+
+        Ok(())
+    }
+
+    // Soulbound pools keep LP accounts frozen, so they are thawed only for the duration of a mint or burn
+    fn set_lp_frozen(&mut self, frozen: bool) -> Result<()> {
+        let cpi_program = self.token_program.to_account_info();
+
+        let mint_x = self.mint_x.key().to_bytes();
+        let mint_y = self.mint_y.key().to_bytes();
+        let seed = self.config.seed.to_le_bytes();
+
+        let seeds = [
+            b"config",
+            mint_x.as_ref(),
+            mint_y.as_ref(),
+            seed.as_ref(),
+            &[self.config.config_bump]
+        ];
+
+        let signer_seeds = &[&seeds[..]];
+
+        match frozen {
+            true => {
+                let cpi_accounts = FreezeAccount {
+                    account: self.lp_provider_ata_lp.to_account_info(),
+                    mint: self.mint_lp.to_account_info(),
+                    authority: self.config.to_account_info(),
+                };
+                freeze_account(CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds))?;
+            }
+            false => {
+                let cpi_accounts = ThawAccount {
+                    account: self.lp_provider_ata_lp.to_account_info(),
+                    mint: self.mint_lp.to_account_info(),
+                    authority: self.config.to_account_info(),
+                };
+                thaw_account(CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds))?;
+            }
+        }
+
+        Ok(())
+    }
+}
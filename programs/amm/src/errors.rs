@@ -15,6 +15,90 @@ pub enum AmmError {
     InsufficientTokenY,
     #[msg("Insufficien Balance")]
     InsufficientBalance,
+    #[msg("Slippage limit exceeded")]
+    SlippageExceeded,
+    #[msg("Unauthorized")]
+    Unauthorized,
+    #[msg("Invalid Fee")]
+    InvalidFee,
+    #[msg("Amount exceeds accrued fees")]
+    ExceedsAccruedFees,
+    #[msg("Mint not allowed")]
+    MintNotAllowed,
+    #[msg("Allowlist is full")]
+    AllowlistFull,
+    #[msg("Invalid Fee Receiver")]
+    InvalidFeeReceiver,
+    #[msg("Signer is not an approved delegate for the amount")]
+    InvalidDelegate,
+    #[msg("Calling program not allowed by the pool")]
+    CallerNotAllowed,
+    #[msg("Epoch fee distribution is disabled")]
+    EpochsDisabled,
+    #[msg("Epoch has not ended yet")]
+    EpochNotEnded,
+    #[msg("Claim the fees of closed epochs first")]
+    UnclaimedEpochFees,
+    #[msg("Position is not eligible for this epoch")]
+    NotEligible,
+    #[msg("No payout is pending")]
+    NoPendingPayout,
+    #[msg("Timelock has not expired")]
+    TimelockActive,
+    #[msg("Invalid emission schedule")]
+    InvalidSchedule,
+    #[msg("Deposit exceeds the pool's TVL cap")]
+    TvlCapExceeded,
+    #[msg("Amount is below the pool's dust threshold")]
+    DustAmount,
+    #[msg("Not the pool's successor")]
+    InvalidSuccessor,
+    #[msg("Pool tokens can't be rescued")]
+    MintNotRescuable,
+    #[msg("Mint has an unsupported Token-2022 extension")]
+    UnsupportedMint,
+    #[msg("Swap would move the price outside the pool's band")]
+    PriceOutOfBand,
+    #[msg("Invalid fee discount account")]
+    InvalidDiscountAccount,
+    #[msg("Pool settings are immutable")]
+    ConfigImmutable,
+    #[msg("Deposit exceeds the per-wallet launch cap")]
+    LaunchCapExceeded,
+    #[msg("Launch deposit account required")]
+    MissingLaunchDeposit,
+    #[msg("Withdrawal not requested")]
+    WithdrawNotRequested,
+    #[msg("Pool is not in compliant mode")]
+    NotCompliant,
+    #[msg("Invalid LBP schedule")]
+    InvalidLbpSchedule,
+    #[msg("Pool still holds liquidity")]
+    PoolNotEmpty,
+    #[msg("Pool has been active too recently")]
+    PoolStillActive,
+    #[msg("Oracle TWAP does not cover the full window yet")]
+    OracleUnavailable,
+    #[msg("Transaction swaps this pool both ways")]
+    SandwichDetected,
+    #[msg("LP holder registry account is required")]
+    MissingLpHolders,
+    #[msg("Not supported for pools tracking LP holders")]
+    LpHoldersTracked,
+    #[msg("Reserves are deployed, the strategy has to recall them first")]
+    LiquidityDeployed,
+    #[msg("Deployment exceeds the strategy limit")]
+    DeployLimitExceeded,
+    #[msg("Strategy still holds reserves")]
+    StrategyHoldsReserves,
+    #[msg("Pool is not open for trading")]
+    PoolNotOpen,
+    #[msg("Incentive schedule has not ended")]
+    IncentiveActive,
+    #[msg("Incentive claim window has closed")]
+    IncentiveExpired,
+    #[msg("Withdraw request claim window has closed")]
+    WithdrawRequestExpired,
 }
 
 impl From<CurveError> for AmmError {
@@ -0,0 +1,92 @@
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct ProtocolFeesClaimed {
+    pub config: Pubkey,
+    pub amount_x: u64,
+    pub amount_y: u64,
+    pub remaining_x: u64, // protocol fees of token X still accrued after the claim
+    pub remaining_y: u64, // protocol fees of token Y still accrued after the claim
+}
+
+#[event]
+pub struct SwapExecuted {
+    pub config: Pubkey,
+    pub user: Pubkey, // signer, the owner or its delegate
+    pub owner: Pubkey, // owner of the token accounts
+    pub is_x: bool, // true when X was sold for Y
+    pub amount_in: u64, // deposited into the pool, excluding the platform fee
+    pub amount_out: u64, // sent to the user
+    pub fee: u64, // pool fee, in the input token unless the pool charges it on the output
+    pub platform_fee: u64,
+    pub price: u128, // Q64.64 execution price of X in Y
+    pub slot: u64,
+}
+
+// An exact-output swap executed, next to its SwapExecuted, with the part of the request left unfilled
+#[event]
+pub struct ExactOutputFilled {
+    pub config: Pubkey,
+    pub user: Pubkey,
+    pub is_x: bool, // true when X was sold for Y
+    pub amount_in: u64,
+    pub amount_out: u64, // sent to the user
+    pub unfilled: u64, // requested but not bought, nonzero only with allow_partial
+}
+
+// A compliant-mode pool's authority froze or thawed an LP token account
+#[event]
+pub struct LpAccountFreezeChanged {
+    pub config: Pubkey,
+    pub lp_account: Pubkey,
+    pub owner: Pubkey, // owner of lp_account
+    pub frozen: bool,
+    pub authority: Pubkey,
+}
+
+// An empty, inactive pool's accounts were closed by gc_pool
+#[event]
+pub struct PoolGarbageCollected {
+    pub config: Pubkey,
+    pub caller: Pubkey,
+    pub caller_reward: u64, // lamports of reclaimed rent kept by the caller
+    pub treasury_share: u64, // lamports of reclaimed rent paid to the protocol authority
+}
+
+// Protocol fees were converted into the target mint and sent to the treasury by harvest_fees
+#[event]
+pub struct ProtocolFeesHarvested {
+    pub config: Pubkey,
+    pub caller: Pubkey,
+    pub is_x: bool, // true when X fees were sold for Y
+    pub amount_in: u64, // protocol fees sold through the pool
+    pub amount_out: u64, // target mint bought with them
+    pub amount_sent: u64, // sent to the treasury, amount_out plus the protocol fees already in the target mint
+}
+
+// Reserves were lent to the pool's strategy by deploy_idle or returned by recall_idle
+#[event]
+pub struct IdleLiquidityMoved {
+    pub config: Pubkey,
+    pub strategy: Pubkey,
+    pub is_x: bool,
+    pub amount: u64,
+    pub deployed: bool, // true for deploy_idle, false for recall_idle
+    pub deployed_x: u64, // token X the strategy holds afterwards
+    pub deployed_y: u64, // token Y the strategy holds afterwards
+}
+
+// Logged right before a slippage check fails, in the failed transaction's or simulation's logs,
+// so clients can retry with a bound that fits instead of blindly widening slippage
+#[event]
+pub struct SlippageCheckFailed {
+    pub config: Pubkey,
+    pub user: Pubkey,
+    pub mint: Pubkey, // token the violated bound is on
+    pub computed: u64, // amount the instruction computed, paid out or required
+    pub limit: u64, // the caller's bound it violated
+}
+
+pub fn log_slippage(config: Pubkey, user: Pubkey, mint: Pubkey, computed: u64, limit: u64) {
+    emit!(SlippageCheckFailed { config, user, mint, computed, limit });
+}
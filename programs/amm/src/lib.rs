@@ -1,10 +1,16 @@
 use anchor_lang::prelude::*;
 
-mod state;
-mod contexts;
-mod errors;
+pub mod state;
+pub mod contexts;
+pub mod errors;
+pub mod events;
+pub mod constants;
+pub mod math;
+pub mod quote;
 
 use contexts::*;
+use state::{CpiPolicy, DiscountTier};
+use constants::TRANSFER_HOOK_EXECUTE_DISCRIMINATOR;
 
 declare_id!("3FqHinWiuVAhvL8o9MWeZAny2a6BqtEYqxTTcFS84Sqa");
 
@@ -12,13 +18,237 @@ declare_id!("3FqHinWiuVAhvL8o9MWeZAny2a6BqtEYqxTTcFS84Sqa");
 pub mod amm {
     use super::*;
 
-    pub fn initialize(ctx: Context<Initialize>, seed: u64, fee: u16, authority: Option<Pubkey>) -> Result<()> {
-        ctx.accounts.init(seed, fee, authority, &ctx.bumps)?;
+    #[allow(clippy::too_many_arguments)]
+    pub fn initialize(
+        ctx: Context<Initialize>,
+        seed: u64,
+        fee: u16,
+        fee_on_output: bool,
+        soulbound: bool,
+        compliant: bool,
+        track_lp_holders: bool,
+        open_time: i64,
+        authority: Option<Pubkey>,
+    ) -> Result<()> {
+        ctx.accounts.init(seed, fee, fee_on_output, soulbound, compliant, track_lp_holders, open_time, authority, &ctx.bumps)?;
         Ok(())
     }
 
-    pub fn deposit(ctx: Context<Deposit>, lp_amount: u64, max_x:u64, max_y:u64) -> Result<()> {
-        ctx.accounts.deposit(lp_amount, max_x, max_y)?;
+    // Test pools in one call, only in builds with the devnet feature
+    #[cfg(feature = "devnet")]
+    pub fn seed_test_pool(ctx: Context<SeedTestPool>, seed: u64, fee: u16, reserve_x: u64, reserve_y: u64) -> Result<()> {
+        ctx.accounts.seed(seed, fee, reserve_x, reserve_y)?;
+        Ok(())
+    }
+
+    pub fn initialize_lp_holders(ctx: Context<InitializeLpHolders>) -> Result<()> {
+        ctx.accounts.init(&ctx.bumps)?;
+        Ok(())
+    }
+
+    // Token-2022 transfer hook of LP mints of pools tracking LP holders
+    #[instruction(discriminator = TRANSFER_HOOK_EXECUTE_DISCRIMINATOR)]
+    pub fn transfer_hook(ctx: Context<TransferHook>, amount: u64) -> Result<()> {
+        ctx.accounts.execute(amount)?;
+        Ok(())
+    }
+
+    pub fn initialize_global(ctx: Context<InitializeGlobal>, authority: Pubkey, protocol_fee: u16) -> Result<()> {
+        ctx.accounts.init(authority, protocol_fee, &ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn set_curated(ctx: Context<UpdateGlobal>, curated: bool) -> Result<()> {
+        ctx.accounts.set_curated(curated)?;
+        Ok(())
+    }
+
+    pub fn set_max_platform_fee(ctx: Context<UpdateGlobal>, max_platform_fee: u16) -> Result<()> {
+        ctx.accounts.set_max_platform_fee(max_platform_fee)?;
+        Ok(())
+    }
+
+    pub fn set_insurance_fee(ctx: Context<UpdateGlobal>, insurance_fee: u16) -> Result<()> {
+        ctx.accounts.set_insurance_fee(insurance_fee)?;
+        Ok(())
+    }
+
+    pub fn set_fee_target_mint(ctx: Context<UpdateGlobal>, mint: Pubkey) -> Result<()> {
+        ctx.accounts.set_fee_target_mint(mint)?;
+        Ok(())
+    }
+
+    pub fn add_allowed_quote_mint(ctx: Context<UpdateGlobal>, mint: Pubkey) -> Result<()> {
+        ctx.accounts.add_allowed_quote_mint(mint)?;
+        Ok(())
+    }
+
+    pub fn remove_allowed_quote_mint(ctx: Context<UpdateGlobal>, mint: Pubkey) -> Result<()> {
+        ctx.accounts.remove_allowed_quote_mint(mint)?;
+        Ok(())
+    }
+
+    pub fn claim_protocol_fees(ctx: Context<ClaimProtocolFees>, amount_x: u64, amount_y: u64) -> Result<()> {
+        ctx.accounts.claim(amount_x, amount_y)?;
+        Ok(())
+    }
+
+    pub fn propose_insurance_payout(ctx: Context<ManageInsurance>, amount_x: u64, amount_y: u64, recipient: Pubkey) -> Result<()> {
+        ctx.accounts.propose_payout(amount_x, amount_y, recipient)?;
+        Ok(())
+    }
+
+    pub fn cancel_insurance_payout(ctx: Context<ManageInsurance>) -> Result<()> {
+        ctx.accounts.cancel_payout()?;
+        Ok(())
+    }
+
+    pub fn execute_insurance_payout(ctx: Context<ExecuteInsurancePayout>) -> Result<()> {
+        ctx.accounts.execute_payout()?;
+        Ok(())
+    }
+
+    pub fn rescue_tokens(ctx: Context<RescueTokens>) -> Result<()> {
+        ctx.accounts.rescue()?;
+        Ok(())
+    }
+
+    pub fn gc_pool(ctx: Context<GcPool>) -> Result<()> {
+        ctx.accounts.gc(&ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn close_orphaned<'info>(ctx: Context<'_, '_, '_, 'info, CloseOrphaned<'info>>) -> Result<()> {
+        ctx.accounts.close_orphaned(ctx.remaining_accounts)?;
+        Ok(())
+    }
+
+    pub fn harvest_fees(ctx: Context<HarvestFees>) -> Result<()> {
+        ctx.accounts.harvest()?;
+        Ok(())
+    }
+
+    pub fn lock(ctx: Context<UpdateConfig>, auto_unlock_ts: i64) -> Result<()> {
+        ctx.accounts.lock(auto_unlock_ts)?;
+        Ok(())
+    }
+
+    pub fn unlock(ctx: Context<UpdateConfig>) -> Result<()> {
+        ctx.accounts.unlock()?;
+        Ok(())
+    }
+
+    pub fn freeze_lp_account(ctx: Context<ComplianceFreeze>) -> Result<()> {
+        ctx.accounts.set_frozen(true)?;
+        Ok(())
+    }
+
+    pub fn thaw_lp_account(ctx: Context<ComplianceFreeze>) -> Result<()> {
+        ctx.accounts.set_frozen(false)?;
+        Ok(())
+    }
+
+    pub fn set_launch_fee(ctx: Context<UpdateConfig>, launch_fee: u16, slots: u64, on_x: bool) -> Result<()> {
+        ctx.accounts.set_launch_fee(launch_fee, slots, on_x)?;
+        Ok(())
+    }
+
+    pub fn set_cpi_policy(ctx: Context<UpdateConfig>, policy: CpiPolicy, programs: Vec<Pubkey>) -> Result<()> {
+        ctx.accounts.set_cpi_policy(policy, programs)?;
+        Ok(())
+    }
+
+    pub fn set_discount_tiers(ctx: Context<UpdateConfig>, mint: Pubkey, tiers: Vec<DiscountTier>) -> Result<()> {
+        ctx.accounts.set_discount_tiers(mint, tiers)?;
+        Ok(())
+    }
+
+    pub fn set_epoch_duration(ctx: Context<UpdateConfig>, epoch_duration: i64) -> Result<()> {
+        ctx.accounts.set_epoch_duration(epoch_duration)?;
+        Ok(())
+    }
+
+    pub fn set_max_lp_supply(ctx: Context<UpdateConfig>, max_lp_supply: u64) -> Result<()> {
+        ctx.accounts.set_max_lp_supply(max_lp_supply)?;
+        Ok(())
+    }
+
+    pub fn set_launch_deposit_cap(ctx: Context<UpdateConfig>, cap: u64, end_ts: i64) -> Result<()> {
+        ctx.accounts.set_launch_deposit_cap(cap, end_ts)?;
+        Ok(())
+    }
+
+    pub fn set_withdraw_cooldown(ctx: Context<UpdateConfig>, secs: i64) -> Result<()> {
+        ctx.accounts.set_withdraw_cooldown(secs)?;
+        Ok(())
+    }
+
+    pub fn set_dust_thresholds(ctx: Context<UpdateConfig>, min_swap_amount: u64, min_withdraw_lp: u64) -> Result<()> {
+        ctx.accounts.set_dust_thresholds(min_swap_amount, min_withdraw_lp)?;
+        Ok(())
+    }
+
+    pub fn set_price_band(ctx: Context<UpdateConfig>, min_price: u128, max_price: u128, partial_fill: bool) -> Result<()> {
+        ctx.accounts.set_price_band(min_price, max_price, partial_fill)?;
+        Ok(())
+    }
+
+    pub fn set_lbp_schedule(ctx: Context<UpdateConfig>, start_weight_x: u16, end_weight_x: u16, start_ts: i64, end_ts: i64) -> Result<()> {
+        ctx.accounts.set_lbp_schedule(start_weight_x, end_weight_x, start_ts, end_ts)?;
+        Ok(())
+    }
+
+    pub fn set_sandwich_guard(ctx: Context<UpdateConfig>, enabled: bool) -> Result<()> {
+        ctx.accounts.set_sandwich_guard(enabled)?;
+        Ok(())
+    }
+
+    pub fn set_strategy(ctx: Context<UpdateConfig>, strategy: Option<Pubkey>, max_deployed_bps: u16) -> Result<()> {
+        ctx.accounts.set_strategy(strategy, max_deployed_bps)?;
+        Ok(())
+    }
+
+    pub fn deploy_idle(ctx: Context<IdleLiquidity>, is_x: bool, amount: u64) -> Result<()> {
+        ctx.accounts.deploy(is_x, amount)?;
+        Ok(())
+    }
+
+    pub fn recall_idle(ctx: Context<IdleLiquidity>, is_x: bool, amount: u64) -> Result<()> {
+        ctx.accounts.recall(is_x, amount)?;
+        Ok(())
+    }
+
+    pub fn resize_config(ctx: Context<ResizeConfig>) -> Result<()> {
+        ctx.accounts.resize()?;
+        Ok(())
+    }
+
+    pub fn set_trading_windows(ctx: Context<UpdateConfig>, period: i64, open_secs: i64) -> Result<()> {
+        ctx.accounts.set_trading_windows(period, open_secs)?;
+        Ok(())
+    }
+
+    pub fn set_successor(ctx: Context<UpdateConfig>, successor: Option<Pubkey>) -> Result<()> {
+        ctx.accounts.set_successor(successor)?;
+        Ok(())
+    }
+
+    pub fn renounce_authority(ctx: Context<UpdateConfig>) -> Result<()> {
+        ctx.accounts.renounce_authority()?;
+        Ok(())
+    }
+
+    pub fn deposit(ctx: Context<Deposit>, max_x: u64, max_y: u64, min_lp: u64) -> Result<()> {
+        ctx.accounts.deposit(max_x, max_y, min_lp, &ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn request_withdraw(ctx: Context<RequestWithdraw>, lp_amount: u64) -> Result<()> {
+        ctx.accounts.request(lp_amount, &ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn close_withdraw_request(_ctx: Context<CloseWithdrawRequest>) -> Result<()> {
         Ok(())
     }
 
@@ -27,9 +257,76 @@ pub mod amm {
         Ok(())
     }
 
-    pub fn swap(ctx: Context<Swap>, is_x: bool, amount: u64, min: u64) -> Result<()> {
-        let args = SwapArgs { is_x, amount, min };
+    pub fn withdraw_all(ctx: Context<Withdraw>, min_x: u64, min_y: u64) -> Result<()> {
+        ctx.accounts.withdraw_all(min_x, min_y)?;
+        Ok(())
+    }
+
+    pub fn migrate_liquidity(ctx: Context<MigrateLiquidity>, lp_amount: u64, min_lp_out: u64) -> Result<()> {
+        ctx.accounts.migrate(lp_amount, min_lp_out)?;
+        Ok(())
+    }
+
+    pub fn stake_lp(ctx: Context<StakeLp>, amount: u64) -> Result<()> {
+        ctx.accounts.stake(amount, &ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn unstake_lp(ctx: Context<StakeLp>, amount: u64) -> Result<()> {
+        ctx.accounts.unstake(amount)?;
+        Ok(())
+    }
+
+    pub fn advance_epoch(ctx: Context<AdvanceEpoch>) -> Result<()> {
+        ctx.accounts.advance(&ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn claim_epoch_fees(ctx: Context<ClaimEpochFees>) -> Result<()> {
+        ctx.accounts.claim()?;
+        Ok(())
+    }
+
+    pub fn create_incentive(ctx: Context<CreateIncentive>, amount: u64, start_ts: i64, end_ts: i64) -> Result<()> {
+        ctx.accounts.create(amount, start_ts, end_ts, &ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn claim_incentive(ctx: Context<ClaimIncentive>) -> Result<()> {
+        ctx.accounts.claim(&ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn reclaim_incentive<'info>(ctx: Context<'_, '_, '_, 'info, ReclaimIncentive<'info>>) -> Result<()> {
+        ctx.accounts.reclaim(ctx.remaining_accounts)?;
+        Ok(())
+    }
+
+    pub fn update_observation(ctx: Context<UpdateObservation>) -> Result<()> {
+        ctx.accounts.update_observation(&ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn get_virtual_price(ctx: Context<PoolView>) -> Result<VirtualPrice> {
+        ctx.accounts.virtual_price()
+    }
+
+    pub fn get_pool_info(ctx: Context<PoolView>) -> Result<PoolInfo> {
+        ctx.accounts.pool_info()
+    }
+
+    pub fn swap(ctx: Context<Swap>, is_x: bool, amount: u64, min: u64, platform_fee: u16) -> Result<()> {
+        let args = SwapArgs { is_x, amount, min, platform_fee };
         ctx.accounts.swap(args)?;
         Ok(())
     }
+
+    pub fn swap_exact_out(ctx: Context<Swap>, is_x: bool, amount_out: u64, max_in: u64, allow_partial: bool) -> Result<ExactOutputFill> {
+        ctx.accounts.swap_exact_out(is_x, amount_out, max_in, allow_partial)
+    }
+
+    pub fn swap_best<'info>(ctx: Context<'_, '_, '_, 'info, SwapBest<'info>>, is_x: bool, amount: u64, min: u64) -> Result<()> {
+        ctx.accounts.swap_best(ctx.remaining_accounts, is_x, amount, min)?;
+        Ok(())
+    }
 }
\ No newline at end of file
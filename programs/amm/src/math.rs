@@ -0,0 +1,266 @@
+// Price of one unit of the base token in units of the quote token, as a Q64.64 fixed point number
+pub fn q64_price(base: u64, quote: u64) -> Option<u128> {
+    if base == 0 {
+        return None;
+    }
+
+    // A u64 shifted by 64 bits always fits in a u128
+    Some(((quote as u128) << 64) / base as u128)
+}
+
+// a * b / c for a u128 b and c, with the product held in 192 bits so it can't overflow. None if c
+// is zero or the result doesn't fit a u64.
+pub fn mul_div_wide(a: u64, b: u128, c: u128, round_up: bool) -> Option<u64> {
+    u64::try_from(mul_div_u128(a as u128, b, c, round_up)?).ok()
+}
+
+// Same for a u128 a, with the product in 256 bits, for share amounts that don't fit a u64
+pub fn mul_div_u128(a: u128, b: u128, c: u128, round_up: bool) -> Option<u128> {
+    if c == 0 {
+        return None;
+    }
+
+    // Product as four 64 bit limbs, most significant first
+    let (a_high, a_low) = (a >> 64, a as u64 as u128);
+    let (b_high, b_low) = (b >> 64, b as u64 as u128);
+    let low = a_low * b_low;
+    let cross_1 = a_low * b_high;
+    let cross_2 = a_high * b_low;
+    let top = a_high * b_high;
+    let mid = (low >> 64) + (cross_1 as u64 as u128) + (cross_2 as u64 as u128);
+    let high = (mid >> 64) + (cross_1 >> 64) + (cross_2 >> 64) + (top as u64 as u128);
+    let limbs = [((high >> 64) + (top >> 64)) as u64, high as u64, mid as u64, low as u64];
+
+    // Schoolbook binary long division, `carry` is the 129th bit of the remainder
+    let mut quotient: u128 = 0;
+    let mut remainder: u128 = 0;
+
+    for limb in limbs {
+        for bit in (0..64).rev() {
+            let carry = remainder >> 127 == 1;
+            remainder = (remainder << 1) | ((limb >> bit) & 1) as u128;
+
+            if quotient >> 127 == 1 {
+                return None;
+            }
+            quotient <<= 1;
+
+            if carry || remainder >= c {
+                remainder = remainder.wrapping_sub(c);
+                quotient |= 1;
+            }
+        }
+    }
+
+    match round_up && remainder > 0 {
+        true => quotient.checked_add(1),
+        false => Some(quotient),
+    }
+}
+
+// Largest integer whose square doesn't exceed n
+pub const fn isqrt(n: u128) -> u64 {
+    if n == 0 {
+        return 0;
+    }
+
+    // Newton's method from an initial guess above the root, converges downwards
+    let mut x = 1u128 << (128 - n.leading_zeros()).div_ceil(2);
+    loop {
+        let y = (x + n / x) / 2;
+        if y >= x {
+            return x as u64;
+        }
+        x = y;
+    }
+}
+
+// Q62 fixed point for the weighted pool math, so squares of values below 2 still fit a u128
+const Q62: u128 = 1 << 62;
+// Fraction bits of the exponent exp2_neg_q62 evaluates, later ones move the result less than WEIGHTED_MARGIN
+const EXP2_BITS: usize = 48;
+// 2^(-2^-(i + 1)) in Q62 at index i, by repeated square roots of 1/2
+const EXP2_NEG_TABLE: [u128; EXP2_BITS] = exp2_neg_table();
+// Added to the power in weighted_swap_output, covering the truncation in log2 and exp2
+const WEIGHTED_MARGIN: u128 = Q62 >> 40;
+
+const fn exp2_neg_table() -> [u128; EXP2_BITS] {
+    let mut table = [0u128; EXP2_BITS];
+    let mut value = Q62 / 2;
+    let mut i = 0;
+    while i < EXP2_BITS {
+        value = isqrt(value * Q62) as u128;
+        table[i] = value;
+        i += 1;
+    }
+    table
+}
+
+// -log2 of x / 2^62 for 0 < x <= 2^62, in Q62 and truncated
+fn neg_log2_q62(x: u128) -> u128 {
+    // Normalized to m in [1, 2), log2(x) = log2(m) - shift
+    let shift = (x.leading_zeros() - Q62.leading_zeros()) as u128;
+    let mut m = x << shift;
+
+    // Squaring m doubles its log2, every time it reaches 2 the next fraction bit is set
+    let mut frac = 0u128;
+    for bit in (0..62).rev() {
+        m = (m * m) >> 62;
+        if m >= 2 * Q62 {
+            m >>= 1;
+            frac |= 1 << bit;
+        }
+    }
+
+    (shift << 62) - frac
+}
+
+// 2^(-y / 2^62) in Q62, truncated
+fn exp2_neg_q62(y: u128) -> u128 {
+    let int = y >> 62;
+    if int >= 62 {
+        return 0;
+    }
+
+    let mut result = Q62;
+    for (i, factor) in EXP2_NEG_TABLE.iter().enumerate() {
+        if (y >> (61 - i)) & 1 == 1 {
+            result = (result * factor) >> 62;
+        }
+    }
+
+    result >> int
+}
+
+// Every helper below rounds in the pool's favor: amounts owed to the pool up, amounts paid out down
+
+// Fee of `fee` basis points on `amount`, rounded up
+pub fn fee_amount(amount: u64, fee: u16) -> Option<u64> {
+    mul_div_wide(amount, fee as u128, 10_000, true)
+}
+
+// Constant product output for `amount_in` after fees, rounded down so k never decreases
+pub fn swap_output(reserve_in: u64, reserve_out: u64, amount_in: u64) -> Option<u64> {
+    mul_div_wide(reserve_out, amount_in as u128, reserve_in as u128 + amount_in as u128, false)
+}
+
+// Weighted pool output for `amount_in` after fees, reserve_out * (1 - (reserve_in / (reserve_in +
+// amount_in)) ^ (weight_in / weight_out)). The power is rounded up so the output rounds down.
+pub fn weighted_swap_output(reserve_in: u64, weight_in: u16, reserve_out: u64, weight_out: u16, amount_in: u64) -> Option<u64> {
+    if reserve_in == 0 || weight_in == 0 || weight_out == 0 {
+        return None;
+    }
+
+    let base = mul_div_wide(reserve_in, Q62, reserve_in as u128 + amount_in as u128, true)? as u128;
+    let exponent = neg_log2_q62(base) * weight_in as u128 / weight_out as u128;
+    let power = (exp2_neg_q62(exponent) + WEIGHTED_MARGIN).min(Q62);
+
+    mul_div_wide(reserve_out, Q62 - power, Q62, false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mul_div_wide_rounds_in_the_requested_direction() {
+        assert_eq!(mul_div_wide(10, 1, 3, false), Some(3));
+        assert_eq!(mul_div_wide(10, 1, 3, true), Some(4));
+        // Exact quotients are never bumped
+        assert_eq!(mul_div_wide(9, 1, 3, true), Some(3));
+        assert_eq!(mul_div_wide(0, 7, 3, true), Some(0));
+    }
+
+    #[test]
+    fn mul_div_wide_handles_products_above_u128() {
+        let b = u128::MAX / 3;
+        assert_eq!(mul_div_wide(u64::MAX, b, b, false), Some(u64::MAX));
+        assert_eq!(mul_div_wide(u64::MAX, u128::MAX, u128::MAX, true), Some(u64::MAX));
+        assert_eq!(mul_div_wide(u64::MAX, u128::MAX - 1, u128::MAX, false), Some(u64::MAX - 1));
+        assert_eq!(mul_div_wide(u64::MAX, u128::MAX - 1, u128::MAX, true), Some(u64::MAX));
+    }
+
+    #[test]
+    fn mul_div_wide_rejects_zero_divisor_and_overflow() {
+        assert_eq!(mul_div_wide(1, 1, 0, false), None);
+        assert_eq!(mul_div_wide(u64::MAX, 2, 1, false), None);
+        // Rounding up past u64::MAX overflows too
+        assert_eq!(mul_div_wide(u64::MAX, u64::MAX as u128 + 1, u64::MAX as u128, true), None);
+    }
+
+    #[test]
+    fn mul_div_u128_handles_products_above_u192() {
+        assert_eq!(mul_div_u128(u128::MAX, u128::MAX, u128::MAX, false), Some(u128::MAX));
+        assert_eq!(mul_div_u128(u128::MAX, 3, 6, true), Some(u128::MAX / 2 + 1));
+        assert_eq!(mul_div_u128(1 << 100, 1 << 99, 1 << 72, false), Some(1 << 127));
+        assert_eq!(mul_div_u128(1 << 100, 1 << 100, 1 << 72, false), None);
+        assert_eq!(mul_div_u128(u128::MAX, 2, 1, false), None);
+        assert_eq!(mul_div_u128(1, 1, 0, false), None);
+    }
+
+    #[test]
+    fn fee_rounds_up() {
+        assert_eq!(fee_amount(1, 30), Some(1));
+        assert_eq!(fee_amount(0, 30), Some(0));
+        assert_eq!(fee_amount(10_000, 30), Some(30));
+        assert_eq!(fee_amount(10_001, 30), Some(31));
+        assert_eq!(fee_amount(1_000, 0), Some(0));
+    }
+
+    #[test]
+    fn swap_output_rounds_down_and_keeps_k() {
+        assert_eq!(swap_output(1_000, 1_000, 1), Some(0));
+        assert_eq!(swap_output(1_000, 1_000, 1_000), Some(500));
+        assert_eq!(swap_output(3, 10, 1), Some(2));
+
+        for (reserve_in, reserve_out, amount_in) in [
+            (1, 1, 1),
+            (7, 1_000_000, 3),
+            (1_000_000, 7, 999_999),
+            (u64::MAX / 2, u64::MAX / 3, 12_345),
+            (123_456_789, 987_654_321, u64::MAX / 4),
+        ] {
+            let out = swap_output(reserve_in, reserve_out, amount_in).unwrap();
+            let k = reserve_in as u128 * reserve_out as u128;
+            let k_after = (reserve_in as u128 + amount_in as u128) * (reserve_out - out) as u128;
+            assert!(out < reserve_out || reserve_out == 0);
+            assert!(k_after >= k);
+        }
+    }
+
+    #[test]
+    fn deposit_then_withdraw_never_returns_more() {
+        for (reserve, total, shares) in [(1, 3, 1), (1_000, 7, 3), (999, 1_000, 1), (u64::MAX / 4, 3, 2), (5, u128::MAX - 1, 1)] {
+            let paid = mul_div_wide(reserve, shares, total, true).unwrap();
+            let reserve_after = reserve + paid;
+            let received = mul_div_wide(reserve_after, shares, total + shares, false).unwrap();
+            assert!(received <= paid);
+        }
+    }
+
+    #[test]
+    fn isqrt_floors_the_root() {
+        assert_eq!(isqrt(0), 0);
+        assert_eq!(isqrt(1), 1);
+        assert_eq!(isqrt(15), 3);
+        assert_eq!(isqrt(16), 4);
+        assert_eq!(isqrt(u128::MAX), u64::MAX);
+        assert_eq!(isqrt((u64::MAX as u128) * (u64::MAX as u128) - 1), u64::MAX - 1);
+    }
+
+
+    #[test]
+    fn weighted_swap_output_matches_the_curve() {
+        // Equal weights are the constant product curve, give or take the rounding margin
+        let constant_product = swap_output(1_000_000, 4_000_000, 10_000).unwrap();
+        let weighted = weighted_swap_output(1_000_000, 5_000, 4_000_000, 5_000, 10_000).unwrap();
+        assert!(weighted <= constant_product && weighted + 1 >= constant_product);
+
+        // 80/20: 1_000_000 * (1 - (1 / 1.01)^4) = 39_019.6
+        assert_eq!(weighted_swap_output(1_000_000, 8_000, 1_000_000, 2_000, 10_000), Some(39_019));
+        // 20/80: 1_000_000 * (1 - (1 / 1.01)^0.25) = 2_484.5
+        assert_eq!(weighted_swap_output(1_000_000, 2_000, 1_000_000, 8_000, 10_000), Some(2_484));
+        assert_eq!(weighted_swap_output(1_000_000, 5_000, 1_000_000, 5_000, 0), Some(0));
+    }
+
+}
@@ -0,0 +1,331 @@
+// Pricing as pure functions of the pool state, the exact code swap, deposit and withdraw execute.
+// Off-chain simulators and bots can call them through the program crate, built with the
+// no-entrypoint feature, and get bit-for-bit the amounts the program would.
+
+use crate::constants::{LP_SHARE_SCALE, MINIMUM_LIQUIDITY};
+use crate::math::{fee_amount, isqrt, mul_div_u128, mul_div_wide, swap_output, weighted_swap_output};
+
+// The parts of a pool's state that deposits and withdrawals are priced from
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PoolState {
+    pub reserve_x: u64, // trading reserves, the vault balances minus every booked fee balance
+    pub reserve_y: u64,
+    pub lp_fees_x: u64, // LP fees held apart from the reserves
+    pub lp_fees_y: u64,
+    pub total_shares: u128,
+    pub lp_supply: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SwapQuote {
+    pub amount_in: u64, // deposited into the pool
+    pub amount_out: u64, // paid to the swapper
+    pub fee: u64, // in the input token, or the output token when the fee is on the output
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LiquidityQuote {
+    pub amount_x: u64, // total token X paid in or out, including fee_x
+    pub amount_y: u64, // total token Y paid in or out, including fee_y
+    pub fee_x: u64, // part of amount_x booked to or from the LP fee balance
+    pub fee_y: u64, // part of amount_y booked to or from the LP fee balance
+    pub shares: u128, // internal shares created or redeemed
+    pub lp_amount: u64, // LP tokens minted or burned for them
+}
+
+// LP amount the shares are spread over: the LP supply plus MINIMUM_LIQUIDITY the first deposit locked
+// for good. Zero before the first deposit.
+fn share_supply(pool: &PoolState) -> u128 {
+    match pool.total_shares {
+        0 => 0,
+        _ => pool.lp_supply as u128 + MINIMUM_LIQUIDITY as u128,
+    }
+}
+
+// Swap of `amount_in` against the reserves at `fee` basis points. The fee rounds up and the output
+// down. With fee_on_output the fee is carved out of the output instead of the input.
+pub fn swap_quote(reserve_in: u64, reserve_out: u64, amount_in: u64, fee: u16, fee_on_output: bool) -> Option<SwapQuote> {
+    quote_with(amount_in, fee, fee_on_output, |amount| swap_output(reserve_in, reserve_out, amount))
+}
+
+// Same for a weighted (LBP) pool, weights in basis points
+pub fn weighted_swap_quote(
+    reserve_in: u64,
+    weight_in: u16,
+    reserve_out: u64,
+    weight_out: u16,
+    amount_in: u64,
+    fee: u16,
+    fee_on_output: bool,
+) -> Option<SwapQuote> {
+    quote_with(amount_in, fee, fee_on_output, |amount| {
+        weighted_swap_output(reserve_in, weight_in, reserve_out, weight_out, amount)
+    })
+}
+
+fn quote_with(amount_in: u64, fee: u16, fee_on_output: bool, output: impl Fn(u64) -> Option<u64>) -> Option<SwapQuote> {
+    match fee_on_output {
+        false => {
+            let fee = fee_amount(amount_in, fee)?;
+            let amount_out = output(amount_in.checked_sub(fee)?)?;
+            Some(SwapQuote { amount_in, amount_out, fee })
+        }
+        true => {
+            let gross = output(amount_in)?;
+            let fee = fee_amount(gross, fee)?;
+            Some(SwapQuote { amount_in, amount_out: gross - fee, fee })
+        }
+    }
+}
+
+// Smallest input up to `max_in` that `quote` turns into at least `amount_out`, None if even
+// `max_in` falls short. A binary search over the exact forward quote, so exact-output swaps pay the
+// same as the exact-input swap they execute as, on any curve and fee setting.
+pub fn input_for_output(amount_out: u64, max_in: u64, quote: impl Fn(u64) -> Option<SwapQuote>) -> Option<u64> {
+    let covers = |amount_in: u64| quote(amount_in).is_some_and(|quote| quote.amount_out >= amount_out);
+    if !covers(max_in) {
+        return None;
+    }
+
+    // Outputs only grow with the input, `high` always covers and `low` never does
+    let (mut low, mut high) = (0, max_in);
+    while high - low > 1 {
+        let mid = low + (high - low) / 2;
+        match covers(mid) {
+            true => high = mid,
+            false => low = mid,
+        }
+    }
+
+    Some(high)
+}
+
+// Deposit of up to `max_x` and `max_y`. Shares are priced from the value paid in against the
+// shares outstanding and the LP tokens derived from the shares, both rounded down, while the amounts
+// paid round up. New LPs also buy into the LP fees already earned.
+//
+// The first deposit takes both amounts, together with anything sent to the vaults before it, and
+// mints the geometric mean minus MINIMUM_LIQUIDITY, which stays locked so the share price can't be
+// inflated from a few units.
+pub fn deposit_amounts(pool: &PoolState, max_x: u64, max_y: u64) -> Option<LiquidityQuote> {
+    if pool.total_shares == 0 {
+        let value_x = pool.reserve_x.checked_add(max_x)?;
+        let value_y = pool.reserve_y.checked_add(max_y)?;
+        let lp_total = isqrt(value_x as u128 * value_y as u128);
+
+        return Some(LiquidityQuote {
+            amount_x: max_x,
+            amount_y: max_y,
+            fee_x: 0,
+            fee_y: 0,
+            shares: lp_total as u128 * LP_SHARE_SCALE,
+            lp_amount: lp_total.saturating_sub(MINIMUM_LIQUIDITY),
+        });
+    }
+
+    let value_x = pool.reserve_x as u128 + pool.lp_fees_x as u128;
+    let value_y = pool.reserve_y as u128 + pool.lp_fees_y as u128;
+    let shares = mul_div_u128(max_x as u128, pool.total_shares, value_x, false)?
+        .min(mul_div_u128(max_y as u128, pool.total_shares, value_y, false)?);
+
+    // The fee part rounds down so the reserves get the rounding, the total still rounds up
+    let amount = |value: u128| u64::try_from(mul_div_u128(shares, value, pool.total_shares, true)?).ok();
+    let fee = |amount: u64| mul_div_wide(amount, shares, pool.total_shares, false);
+
+    Some(LiquidityQuote {
+        amount_x: amount(value_x)?,
+        amount_y: amount(value_y)?,
+        fee_x: fee(pool.lp_fees_x)?,
+        fee_y: fee(pool.lp_fees_y)?,
+        shares,
+        lp_amount: u64::try_from(mul_div_u128(shares, share_supply(pool), pool.total_shares, false)?).ok()?,
+    })
+}
+
+// Tokens burning `lp_amount` LP tokens pays out, rounded down, with the LP's share of the LP fees
+pub fn withdraw_amounts(pool: &PoolState, lp_amount: u64) -> Option<LiquidityQuote> {
+    if lp_amount > pool.lp_supply {
+        return None;
+    }
+
+    let shares = mul_div_u128(lp_amount as u128, pool.total_shares, share_supply(pool), false)?;
+    let share = |amount: u64| mul_div_wide(amount, shares, pool.total_shares, false);
+
+    let fee_x = share(pool.lp_fees_x)?;
+    let fee_y = share(pool.lp_fees_y)?;
+
+    Some(LiquidityQuote {
+        amount_x: share(pool.reserve_x)?.checked_add(fee_x)?,
+        amount_y: share(pool.reserve_y)?.checked_add(fee_y)?,
+        fee_x,
+        fee_y,
+        shares,
+        lp_amount,
+    })
+}
+
+// How much worse a trade's execution price is than the spot price, in basis points rounded down.
+// Fees count towards the impact since they come out of the same output.
+pub fn price_impact_bps(reserve_in: u64, reserve_out: u64, amount_in: u64, amount_out: u64) -> Option<u64> {
+    let spot_out = amount_in as u128 * reserve_out as u128;
+    let actual_out = amount_out as u128 * reserve_in as u128;
+
+    let kept = mul_div_wide(10_000, actual_out, spot_out, true)?;
+
+    Some(10_000u64.saturating_sub(kept))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POOL: PoolState = PoolState {
+        reserve_x: 1_000_000,
+        reserve_y: 4_000_000,
+        lp_fees_x: 300,
+        lp_fees_y: 1_200,
+        total_shares: 2_000_000 * LP_SHARE_SCALE,
+        lp_supply: 2_000_000 - MINIMUM_LIQUIDITY,
+    };
+
+    const EMPTY: PoolState = PoolState {
+        reserve_x: 0,
+        reserve_y: 0,
+        lp_fees_x: 0,
+        lp_fees_y: 0,
+        total_shares: 0,
+        lp_supply: 0,
+    };
+
+    #[test]
+    fn swap_quote_matches_the_curve() {
+        // 1% fee on 10_000 in leaves 9_900 to trade: 4_000_000 * 9_900 / 1_009_900 = 39_211.8
+        let quote = swap_quote(1_000_000, 4_000_000, 10_000, 100, false).unwrap();
+        assert_eq!(quote, SwapQuote { amount_in: 10_000, amount_out: 39_211, fee: 100 });
+
+        // The full input trades for 39_603.9, the fee comes out of that
+        let quote = swap_quote(1_000_000, 4_000_000, 10_000, 100, true).unwrap();
+        assert_eq!(quote, SwapQuote { amount_in: 10_000, amount_out: 39_206, fee: 397 });
+    }
+
+    #[test]
+    fn input_for_output_finds_the_smallest_covering_input() {
+        for fee_on_output in [false, true] {
+            let quote = |amount: u64| swap_quote(1_000_000, 4_000_000, amount, 30, fee_on_output);
+
+            let amount_in = input_for_output(39_000, u64::MAX >> 16, quote).unwrap();
+            assert!(quote(amount_in).unwrap().amount_out >= 39_000);
+            assert!(quote(amount_in - 1).unwrap().amount_out < 39_000);
+        }
+    }
+
+    #[test]
+    fn input_for_output_is_none_above_max_in() {
+        let quote = |amount: u64| swap_quote(1_000_000, 4_000_000, amount, 30, false);
+        let amount_in = input_for_output(39_000, 1_000_000, quote).unwrap();
+
+        assert_eq!(input_for_output(39_000, amount_in, quote), Some(amount_in));
+        assert_eq!(input_for_output(39_000, amount_in - 1, quote), None);
+        // More than the reserve can never be bought
+        assert_eq!(input_for_output(4_000_000, u64::MAX >> 16, quote), None);
+    }
+
+    #[test]
+    fn deposits_round_up_and_withdrawals_down() {
+        // 3 of 1_000_300 X is worth 5.998 LP, the fraction stays with the pool
+        let deposit = deposit_amounts(&POOL, 3, 12).unwrap();
+        let withdrawal = withdraw_amounts(&POOL, 3).unwrap();
+
+        assert_eq!(
+            deposit,
+            LiquidityQuote { amount_x: 3, amount_y: 12, fee_x: 0, fee_y: 0, shares: 5_998_200_539, lp_amount: 5 }
+        );
+        assert_eq!(
+            withdrawal,
+            LiquidityQuote { amount_x: 1, amount_y: 6, fee_x: 0, fee_y: 0, shares: 3 * LP_SHARE_SCALE, lp_amount: 3 }
+        );
+    }
+
+    #[test]
+    fn deposits_are_priced_by_the_scarcer_side() {
+        let deposit = deposit_amounts(&POOL, 1_000, 4_000).unwrap();
+        assert_eq!((deposit.amount_x, deposit.amount_y, deposit.fee_y, deposit.lp_amount), (1_000, 4_000, 1, 1_999));
+
+        // One Y buys a quarter of a unit of X's worth of shares, less than an LP token
+        let deposit = deposit_amounts(&POOL, 1_000, 1).unwrap();
+        assert_eq!((deposit.amount_x, deposit.amount_y, deposit.lp_amount), (1, 1, 0));
+    }
+
+    #[test]
+    fn withdrawals_are_capped_at_the_lp_supply() {
+        assert!(withdraw_amounts(&POOL, 2_000_000 - MINIMUM_LIQUIDITY).is_some());
+        assert!(withdraw_amounts(&POOL, 2_000_000 - MINIMUM_LIQUIDITY + 1).is_none());
+    }
+
+    #[test]
+    fn first_deposit_locks_minimum_liquidity() {
+        let deposit = deposit_amounts(&EMPTY, 1_000, 4_000).unwrap();
+        assert_eq!(
+            deposit,
+            LiquidityQuote { amount_x: 1_000, amount_y: 4_000, fee_x: 0, fee_y: 0, shares: 2_000 * LP_SHARE_SCALE, lp_amount: 1_000 }
+        );
+
+        // Too small to cover the locked amount
+        assert_eq!(deposit_amounts(&EMPTY, 1_000, 1_000).unwrap().lp_amount, 0);
+
+        // The whole LP supply can leave, the locked part of the reserves stays
+        let pool = PoolState { reserve_x: 1_000, reserve_y: 4_000, total_shares: deposit.shares, lp_supply: 1_000, ..EMPTY };
+        let withdrawal = withdraw_amounts(&pool, 1_000).unwrap();
+        assert_eq!((withdrawal.amount_x, withdrawal.amount_y), (500, 2_000));
+    }
+
+    #[test]
+    fn first_deposit_takes_donations_to_the_vaults() {
+        // Tokens sent to the vaults before any shares exist go to the first LP instead of bricking the pool
+        let pool = PoolState { reserve_x: 500, ..EMPTY };
+        let deposit = deposit_amounts(&pool, 1_000, 4_000).unwrap();
+
+        // sqrt(1_500 * 4_000) = 2_449
+        assert_eq!((deposit.amount_x, deposit.amount_y, deposit.lp_amount), (1_000, 4_000, 1_449));
+        assert_eq!(deposit.shares, 2_449 * LP_SHARE_SCALE);
+    }
+
+    #[test]
+    fn donations_after_the_first_deposit_dont_pay_off() {
+        // The attacker mints a single LP token, then donates to inflate its price
+        let first = deposit_amounts(&EMPTY, 1_001, 1_001).unwrap();
+        assert_eq!(first.lp_amount, 1);
+        let donation = 1_000_000;
+        let mut pool = PoolState {
+            reserve_x: 1_001 + donation,
+            reserve_y: 1_001 + donation,
+            total_shares: first.shares,
+            lp_supply: first.lp_amount,
+            ..EMPTY
+        };
+
+        // The locked liquidity keeps LP tokens cheap, so the next deposit still gets a fair amount
+        let victim = deposit_amounts(&pool, 999_999, 999_999).unwrap();
+        assert_eq!(victim.lp_amount, 999);
+        pool.reserve_x += victim.amount_x;
+        pool.reserve_y += victim.amount_y;
+        pool.total_shares += victim.shares;
+        pool.lp_supply += victim.lp_amount;
+
+        // Less than a thousandth of the victim's deposit is lost to rounding
+        let victim_exit = withdraw_amounts(&pool, victim.lp_amount).unwrap();
+        assert!(victim_exit.amount_x >= victim.amount_x - victim.amount_x / 1_000);
+
+        // And the attacker gets back a fraction of the donation, most of it went to the locked liquidity
+        let attacker_exit = withdraw_amounts(&pool, 1).unwrap();
+        assert!(attacker_exit.amount_x < donation / 500);
+    }
+
+    #[test]
+    fn price_impact_grows_with_size() {
+        assert_eq!(price_impact_bps(1_000_000, 4_000_000, 1_000, 4_000), Some(0));
+        // 39_211 out against 40_000 at spot
+        assert_eq!(price_impact_bps(1_000_000, 4_000_000, 10_000, 39_211), Some(197));
+        assert_eq!(price_impact_bps(1_000_000, 4_000_000, 1_000, 0), Some(10_000));
+    }
+}
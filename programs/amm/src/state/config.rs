@@ -1,25 +1,415 @@
 use anchor_lang::prelude::*;
 
+use crate::errors::AmmError;
+use crate::math::{isqrt, mul_div_wide, q64_price};
+use crate::quote::{swap_quote, weighted_swap_quote, PoolState, SwapQuote};
+
+pub const MAX_CPI_PROGRAMS: usize = 4;
+pub const MAX_DISCOUNT_TIERS: usize = 4;
+
+// Which programs may CPI into swap, direct calls are always allowed
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, PartialEq, Eq)]
+pub enum CpiPolicy {
+    Open,
+    Allowlist, // only the programs in Config::cpi_programs
+    Denylist, // any program except the ones in Config::cpi_programs
+}
+
+// Swappers holding at least `min_balance` of the discount mint pay `discount` basis points less fee
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, PartialEq, Eq)]
+pub struct DiscountTier {
+    pub min_balance: u64,
+    pub discount: u16,
+}
+
 #[account]
+#[derive(InitSpace)]
 pub struct Config {
     pub seed: u64, // seeds to be able to create different pools
     pub authority: Option<Pubkey>, // if we want an authority to lock the config account
     pub mint_x: Pubkey, // Token X
     pub mint_y: Pubkey, // Token Y
     pub fee: u16, // Swap fees in basis point
+    pub fee_on_output: bool, // take the swap fee from the withdrawn token instead of the deposited one
     pub locked: bool,
     pub config_bump: u8, // seed bump for the config account
     pub lp_bump: u8, // seed bump for the lp token account
+    pub protocol_fees_x: u64, // protocol fees of token X held in vault_x, not part of the reserves
+    pub protocol_fees_y: u64, // protocol fees of token Y held in vault_y, not part of the reserves
+    pub price_x_cumulative: u128, // sum of the Q64.64 price of X in Y times the seconds it was held, wraps on overflow
+    pub price_y_cumulative: u128, // sum of the Q64.64 price of Y in X times the seconds it was held, wraps on overflow
+    pub last_observation_ts: i64, // unix timestamp of the last price accumulation
+    pub observations_bump: u8, // seed bump for the observations ring buffer account
+    pub soulbound: bool, // LP token accounts stay frozen so positions can't be transferred
+    pub risk_flags: u8, // RISK_* bits describing dangerous powers over the pooled mints
+    pub lp_fees_x: u64, // LP share of swap fees in token X, held in vault_x apart from the reserves
+    pub lp_fees_y: u64, // LP share of swap fees in token Y, held in vault_y apart from the reserves
+    pub last_trade_price: u128, // Q64.64 execution price of X in Y of the most recent swap
+    pub last_trade_is_x: bool, // direction of the most recent swap, true when X was sold
+    pub last_trade_slot: u64, // slot of the most recent swap, 0 before the first one
+    pub launch_fee: u16, // fee in basis points at the start of the launch schedule
+    pub launch_fee_start_slot: u64, // slot the launch schedule started at
+    pub launch_fee_slots: u64, // slots for the launch fee to decay to the base fee, 0 when unused
+    pub launch_fee_on_x: bool, // the launch fee applies to swaps selling X, otherwise selling Y
+    pub cpi_policy: CpiPolicy, // whether other programs may CPI into swap
+    #[max_len(MAX_CPI_PROGRAMS)]
+    pub cpi_programs: Vec<Pubkey>, // programs the policy applies to, at most MAX_CPI_PROGRAMS
+    pub epoch_duration: i64, // seconds per fee distribution epoch, 0 when LP fees in Y compound instead
+    pub epoch: u64, // index of the running epoch
+    pub epoch_start_ts: i64, // unix timestamp the running epoch started at
+    pub epoch_fees_y: u64, // LP fees in token Y earned by stakers in the running epoch
+    pub unclaimed_epoch_fees_y: u64, // fees of closed epochs not claimed yet
+    pub staked_active: u64, // staked LP tokens earning fees in the running epoch
+    pub staked_pending: u64, // staked LP tokens that start earning in the next epoch
+    pub insurance_x: u64, // insurance fund in token X, held in vault_x apart from the reserves
+    pub insurance_y: u64, // insurance fund in token Y, held in vault_y apart from the reserves
+    pub insurance_payout_x: u64, // token X amount of the proposed insurance payout
+    pub insurance_payout_y: u64, // token Y amount of the proposed insurance payout
+    pub insurance_payout_recipient: Pubkey, // owner of the accounts receiving the proposed payout
+    pub insurance_payout_ts: i64, // unix timestamp the proposed payout unlocks at, 0 when none is pending
+    pub incentive_count: u64, // incentives created for this pool, the next one's index
+    pub oracle_bump: u8, // seed bump for the PriceOracle account
+    pub total_shares: u128, // internal ownership shares outstanding, split over the LP supply plus MINIMUM_LIQUIDITY
+    pub max_lp_supply: u64, // deposits can't take the LP supply above this, 0 when uncapped
+    pub min_swap_amount: u64, // smallest swap input accepted
+    pub min_withdraw_lp: u64, // smallest LP amount a partial withdrawal can burn
+    pub successor: Option<Pubkey>, // pool on the same pair that LPs can migrate their liquidity to
+    pub vault_x_bump: u8, // seed bump for vault_x
+    pub vault_y_bump: u8, // seed bump for vault_y
+    pub min_price: u128, // Q64.64 price of X in Y that swaps selling X can't push the pool below, 0 when unbounded
+    pub max_price: u128, // Q64.64 price of X in Y that swaps selling Y can't push the pool above, 0 when unbounded
+    pub band_partial_fill: bool, // swaps crossing the band are filled up to the bound instead of rejected
+    pub discount_mint: Pubkey, // token whose holders get swap fee discounts, e.g. the LP or a governance token
+    #[max_len(MAX_DISCOUNT_TIERS)]
+    pub discount_tiers: Vec<DiscountTier>, // fee discounts by holding size, at most MAX_DISCOUNT_TIERS
+    pub immutable: bool, // the authority renounced its powers, pool settings can never change again
+    pub launch_deposit_cap: u64, // LP tokens a single wallet can mint until launch_deposit_end_ts, 0 when uncapped
+    pub launch_deposit_end_ts: i64, // unix timestamp the per-wallet launch cap stops applying at
+    pub withdraw_cooldown_secs: i64, // seconds between request_withdraw and the withdrawal, 0 when withdrawals are immediate
+    pub auto_unlock_ts: i64, // unix timestamp a lock lapses at even without the authority, 0 when it doesn't
+    pub compliant: bool, // set at init, lets the authority freeze and thaw individual LP token accounts
+    pub lbp_start_weight_x: u16, // LBP weight of X in basis points at lbp_start_ts, Y weighs the rest
+    pub lbp_end_weight_x: u16, // LBP weight of X in basis points from lbp_end_ts on
+    pub lbp_start_ts: i64, // unix timestamp the LBP weights start moving at
+    pub lbp_end_ts: i64, // unix timestamp the LBP weights stop moving at, 0 for a constant product pool
+    pub sandwich_guard: bool, // swaps fail when another swap in the transaction trades the pool the other way
+    pub track_lp_holders: bool, // the LP mint is a Token-2022 mint whose transfer hook keeps the LpHolders registry
+    pub strategy: Option<Pubkey>, // owner of the token accounts idle reserves are deployed to, None when deploying is off
+    pub max_deployed_bps: u16, // share of each reserve in basis points the strategy can hold at once
+    pub deployed_x: u64, // token X reserves held by the strategy, still counted in the reserves
+    pub deployed_y: u64, // token Y reserves held by the strategy, still counted in the reserves
+    pub open_time: i64, // unix timestamp swaps are accepted from, liquidity can be seeded before
+    pub window_period: i64, // seconds between the starts of recurring trading windows from open_time on, 0 when always open
+    pub window_open_secs: i64, // seconds at the start of every window_period that swaps are accepted in
+    pub strategy_active_ts: i64, // unix timestamp deploy_idle can lend to the strategy under max_deployed_bps from
+    pub reserved: [u8; 32], // room for new fields without resizing the account, always zero
 }
 
-impl Space for Config {
-    // why add a 1 + 32 to the Option<Pubkey> type
-    // Reason: Pubkey is 32 bytes, and Option is 1 byte
-
-    // u8 -> 1 byte
-    // Option -> 1 byte
-    
-    // u16 -> 2 bytes
-    // Option<u16> -> 3 bytes but since this is odd, it must be padded to an even number
-    const INIT_SPACE: usize = 8 + 8 + (1 + 32) + 32 + 32 + 2 + 1 + 1 + 1;
-}
\ No newline at end of file
+impl Config {
+    pub const LEN: usize = 8 + Config::INIT_SPACE;
+
+    // Vault balances minus the fee balances plus what the strategy holds, so pricing and the oracle
+    // only see the trading reserves
+    pub fn reserves(&self, vault_x: u64, vault_y: u64) -> Result<(u64, u64)> {
+        let (x, y) = self.idle_reserves(vault_x, vault_y)?;
+
+        Ok((
+            x.checked_add(self.deployed_x).ok_or(AmmError::InvalidAmount)?,
+            y.checked_add(self.deployed_y).ok_or(AmmError::InvalidAmount)?,
+        ))
+    }
+
+    // The reserves still in the vaults, the most swaps and withdrawals can pay out right now
+    pub fn idle_reserves(&self, vault_x: u64, vault_y: u64) -> Result<(u64, u64)> {
+        let x = vault_x
+            .checked_sub(self.protocol_fees_x)
+            .and_then(|x| x.checked_sub(self.lp_fees_x))
+            .and_then(|x| x.checked_sub(self.insurance_x))
+            .ok_or(AmmError::InsufficientBalance)?;
+        let y = vault_y
+            .checked_sub(self.protocol_fees_y)
+            .and_then(|y| y.checked_sub(self.lp_fees_y))
+            .and_then(|y| y.checked_sub(self.epoch_fees_y))
+            .and_then(|y| y.checked_sub(self.unclaimed_epoch_fees_y))
+            .and_then(|y| y.checked_sub(self.insurance_y))
+            .ok_or(AmmError::InsufficientBalance)?;
+
+        Ok((x, y))
+    }
+
+    // Reserves leaving the pool must be in the vaults, deployed ones come back only through recall_idle
+    pub fn check_idle(&self, vault_x: u64, vault_y: u64, out_x: u64, out_y: u64) -> Result<()> {
+        let (idle_x, idle_y) = self.idle_reserves(vault_x, vault_y)?;
+        require!(out_x <= idle_x && out_y <= idle_y, AmmError::LiquidityDeployed);
+
+        Ok(())
+    }
+
+    // Books a swap fee for the LPs. With epochs enabled LP fees in token Y go to the running
+    // epoch's stakers instead of compounding.
+    pub fn credit_lp_fee(&mut self, in_x: bool, fee: u64) -> Option<()> {
+        let lp_fees = match (in_x, self.epoch_duration > 0) {
+            (true, _) => &mut self.lp_fees_x,
+            (false, false) => &mut self.lp_fees_y,
+            (false, true) => &mut self.epoch_fees_y,
+        };
+        *lp_fees = lp_fees.checked_add(fee)?;
+
+        Some(())
+    }
+
+    // Turning epochs off hands the running epoch's fees back to all LPs, stakers included through
+    // the staked LP tokens, since the epoch would never close for them to be claimed
+    pub fn set_epoch_duration(&mut self, epoch_duration: i64, now: i64) -> Result<()> {
+        require!(epoch_duration >= 0, AmmError::InvalidConfig);
+
+        match (self.epoch_duration > 0, epoch_duration > 0) {
+            (false, true) => self.epoch_start_ts = now,
+            (true, false) => {
+                self.lp_fees_y = self.lp_fees_y.checked_add(self.epoch_fees_y).ok_or(AmmError::InvalidAmount)?;
+                self.epoch_fees_y = 0;
+            },
+            _ => {},
+        }
+        self.epoch_duration = epoch_duration;
+
+        Ok(())
+    }
+
+    // Input for the quote module's deposit and withdrawal pricing
+    pub fn pool_state(&self, reserve_x: u64, reserve_y: u64, lp_supply: u64) -> PoolState {
+        PoolState {
+            reserve_x,
+            reserve_y,
+            lp_fees_x: self.lp_fees_x,
+            lp_fees_y: self.lp_fees_y,
+            total_shares: self.total_shares,
+            lp_supply,
+        }
+    }
+
+    // TWAP accumulators, fed with the reserves that were in effect since the previous observation.
+    // Consumers take the wrapping difference of two snapshots divided by the elapsed seconds.
+    pub fn accumulate_prices(&mut self, reserve_x: u64, reserve_y: u64, now: i64) {
+        let elapsed = now.saturating_sub(self.last_observation_ts);
+
+        if elapsed > 0 {
+            let (reserve_x, reserve_y) = self.price_reserves(reserve_x, reserve_y, now);
+            if let (Some(price_x), Some(price_y)) = (q64_price(reserve_x, reserve_y), q64_price(reserve_y, reserve_x)) {
+                self.price_x_cumulative = self.price_x_cumulative.wrapping_add(price_x.wrapping_mul(elapsed as u128));
+                self.price_y_cumulative = self.price_y_cumulative.wrapping_add(price_y.wrapping_mul(elapsed as u128));
+            }
+            self.last_observation_ts = now;
+        }
+    }
+
+    // Whether reserves after a swap respect the price band. Only the bound the swap moves towards is
+    // checked, so a pool outside the band can always trade back into it.
+    pub fn within_band(&self, is_x: bool, reserve_x: u64, reserve_y: u64, now: i64) -> bool {
+        let (reserve_x, reserve_y) = self.price_reserves(reserve_x, reserve_y, now);
+        let Some(price) = q64_price(reserve_x, reserve_y) else {
+            return false;
+        };
+
+        match is_x {
+            true => price >= self.min_price,
+            false => self.max_price == 0 || price <= self.max_price,
+        }
+    }
+
+    // Most input, after fees, a swap can add before the pool price reaches the band's bound, None
+    // when that side is unbounded. Along x * y = k the price is y / x, so selling X stops once
+    // x'^2 = x * y / min_price and selling Y once y'^2 = x * y * max_price. LBPs don't follow that
+    // curve, so their swaps are never partially filled, only checked against the band.
+    pub fn band_input_limit(&self, is_x: bool, reserve_x: u64, reserve_y: u64) -> Option<u64> {
+        if self.is_lbp() {
+            return None;
+        }
+
+        let (reserve_in, bound) = match is_x {
+            true if self.min_price > 0 => (reserve_x, mul_div_wide(reserve_y, 1 << 64, self.min_price, false)?),
+            false if self.max_price > 0 => (reserve_y, mul_div_wide(reserve_x, self.max_price, 1 << 64, false)?),
+            _ => return None,
+        };
+
+        let limit = isqrt(reserve_in as u128 * bound as u128);
+
+        Some(limit.saturating_sub(reserve_in))
+    }
+
+    pub fn is_lbp(&self) -> bool {
+        self.lbp_end_ts > 0
+    }
+
+    // LBP weights of X and Y in basis points at `now`, moving linearly from the start to the end ratio
+    pub fn weights(&self, now: i64) -> (u16, u16) {
+        let weight_x = match now {
+            now if now <= self.lbp_start_ts => self.lbp_start_weight_x,
+            now if now >= self.lbp_end_ts => self.lbp_end_weight_x,
+            now => {
+                let start = self.lbp_start_weight_x as i64;
+                let end = self.lbp_end_weight_x as i64;
+                let elapsed = now - self.lbp_start_ts;
+                let duration = self.lbp_end_ts - self.lbp_start_ts;
+                (start + (end - start) * elapsed / duration) as u16
+            }
+        };
+
+        (weight_x, 10_000 - weight_x)
+    }
+
+    // Reserves whose ratio is the pool's spot price. A weighted pool prices X at (y / w_y) / (x / w_x),
+    // the ratio of the reserves scaled by the other side's weight.
+    pub fn price_reserves(&self, reserve_x: u64, reserve_y: u64, now: i64) -> (u64, u64) {
+        if !self.is_lbp() {
+            return (reserve_x, reserve_y);
+        }
+
+        let (weight_x, weight_y) = self.weights(now);
+        (
+            (reserve_x as u128 * weight_y as u128 / 10_000) as u64,
+            (reserve_y as u128 * weight_x as u128 / 10_000) as u64,
+        )
+    }
+
+    // Swap against the pool's curve, constant product or the LBP's weights at `now`
+    pub fn swap_quote(&self, is_x: bool, reserve_x: u64, reserve_y: u64, amount: u64, fee: u16, now: i64) -> Option<SwapQuote> {
+        let (reserve_in, reserve_out) = match is_x {
+            true => (reserve_x, reserve_y),
+            false => (reserve_y, reserve_x),
+        };
+
+        if !self.is_lbp() {
+            return swap_quote(reserve_in, reserve_out, amount, fee, self.fee_on_output);
+        }
+
+        let (weight_x, weight_y) = self.weights(now);
+        let (weight_in, weight_out) = match is_x {
+            true => (weight_x, weight_y),
+            false => (weight_y, weight_x),
+        };
+
+        weighted_swap_quote(reserve_in, weight_in, reserve_out, weight_out, amount, fee, self.fee_on_output)
+    }
+
+    // A lock with an auto-unlock time lapses on its own, bounding the damage of a lost or rogue authority
+    pub fn is_locked(&self, now: i64) -> bool {
+        self.locked && (self.auto_unlock_ts == 0 || now < self.auto_unlock_ts)
+    }
+
+    // Makes the settings immutable. Only while unlocked, since nobody could call unlock() afterwards
+    // and the pool would stay frozen for good.
+    pub fn renounce(&mut self, now: i64) -> Result<()> {
+        require!(!self.is_locked(now), AmmError::AMMLocked);
+        self.immutable = true;
+
+        Ok(())
+    }
+
+    // Trading starts at open_time, and with recurring windows only the first window_open_secs of
+    // every window_period after it are open
+    pub fn is_open(&self, now: i64) -> bool {
+        if now < self.open_time {
+            return false;
+        }
+
+        self.window_period == 0 || (now - self.open_time) % self.window_period < self.window_open_secs
+    }
+
+    // Partial exits below min_withdraw_lp are dust, a whole LP balance can always leave
+    pub fn is_dust_withdrawal(&self, lp_amount: u64, lp_balance: u64) -> bool {
+        lp_amount < self.min_withdraw_lp && lp_amount != lp_balance
+    }
+
+    pub fn launch_cap_active(&self, now: i64) -> bool {
+        self.launch_deposit_cap > 0 && now < self.launch_deposit_end_ts
+    }
+
+    // Base fee, or the launch fee decayed linearly towards it while the schedule runs
+    pub fn swap_fee(&self, is_x: bool, slot: u64) -> u16 {
+        let end_slot = self.launch_fee_start_slot.saturating_add(self.launch_fee_slots);
+
+        if is_x != self.launch_fee_on_x || slot >= end_slot {
+            return self.fee;
+        }
+
+        let remaining = (end_slot - slot) as u128;
+        let extra = self.launch_fee.saturating_sub(self.fee) as u128 * remaining / self.launch_fee_slots as u128;
+
+        self.fee + extra as u16
+    }
+
+    // Largest discount of the tiers a `balance` of the discount mint qualifies for
+    pub fn fee_discount(&self, balance: u64) -> u16 {
+        self.discount_tiers
+            .iter()
+            .filter(|tier| balance >= tier.min_balance)
+            .map(|tier| tier.discount)
+            .max()
+            .unwrap_or(0)
+    }
+
+    // `caller` is the program that invoked swap through CPI, None for a direct call
+    pub fn allows_caller(&self, caller: Option<Pubkey>) -> bool {
+        let Some(caller) = caller else {
+            return true;
+        };
+
+        match self.cpi_policy {
+            CpiPolicy::Open => true,
+            CpiPolicy::Allowlist => self.cpi_programs.contains(&caller),
+            CpiPolicy::Denylist => !self.cpi_programs.contains(&caller),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A pool with every field zeroed, as the account reads right after allocation
+    fn config() -> Config {
+        Config::deserialize(&mut &[0u8; Config::INIT_SPACE][..]).unwrap()
+    }
+
+    #[test]
+    fn partial_withdrawals_below_the_threshold_are_dust() {
+        let mut config = config();
+        config.min_withdraw_lp = 100;
+
+        assert!(config.is_dust_withdrawal(99, 1_000));
+        assert!(!config.is_dust_withdrawal(100, 1_000));
+        // Dust positions can still exit in full
+        assert!(!config.is_dust_withdrawal(99, 99));
+    }
+
+    #[test]
+    fn locked_pools_cant_be_renounced() {
+        let mut config = config();
+        config.locked = true;
+
+        assert_eq!(config.renounce(100), Err(AmmError::AMMLocked.into()));
+        assert!(!config.immutable);
+
+        // Once the auto unlock passed nothing can stay frozen
+        config.auto_unlock_ts = 100;
+        assert_eq!(config.renounce(99), Err(AmmError::AMMLocked.into()));
+        assert!(config.renounce(100).is_ok());
+        assert!(config.immutable);
+    }
+
+    #[test]
+    fn disabling_epochs_returns_running_epoch_fees_to_lps() {
+        let mut config = config();
+        config.lp_fees_y = 50;
+
+        assert!(config.set_epoch_duration(3_600, 100).is_ok());
+        assert_eq!(config.epoch_start_ts, 100);
+        config.credit_lp_fee(false, 20).unwrap();
+        assert_eq!((config.lp_fees_y, config.epoch_fees_y), (50, 20));
+
+        assert!(config.set_epoch_duration(0, 200).is_ok());
+        assert_eq!((config.lp_fees_y, config.epoch_fees_y), (70, 0));
+        assert_eq!(config.set_epoch_duration(-1, 200), Err(AmmError::InvalidConfig.into()));
+    }
+}
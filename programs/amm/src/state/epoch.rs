@@ -0,0 +1,35 @@
+use anchor_lang::prelude::*;
+
+// Fees in token Y earned by stakers during one closed epoch
+#[account]
+#[derive(InitSpace)]
+pub struct EpochSnapshot {
+    pub config: Pubkey, // pool the epoch belongs to
+    pub epoch: u64, // epoch index
+    pub fees_y: u64, // LP fees in token Y earned during the epoch
+    pub staked_lp: u64, // LP tokens that were staked for the whole epoch
+    pub claimed_y: u64, // part of fees_y already claimed
+    pub bump: u8, // seed bump for the snapshot account
+    pub start_ts: i64, // epoch start
+    pub end_ts: i64, // epoch end, when it was advanced
+}
+
+impl EpochSnapshot {
+    pub const LEN: usize = 8 + EpochSnapshot::INIT_SPACE;
+}
+
+// LP tokens an owner staked to earn epoch fees
+#[account]
+#[derive(InitSpace)]
+pub struct StakePosition {
+    pub config: Pubkey, // pool the position belongs to
+    pub owner: Pubkey, // owner of the staked LP tokens
+    pub amount: u64, // LP tokens staked
+    pub active_epoch: u64, // first epoch `amount` earns fees in, changing the stake restarts it
+    pub next_claim_epoch: u64, // epochs before this one are claimed or forfeited
+    pub bump: u8, // seed bump for the position account
+}
+
+impl StakePosition {
+    pub const LEN: usize = 8 + StakePosition::INIT_SPACE;
+}
@@ -0,0 +1,29 @@
+use anchor_lang::prelude::*;
+
+pub const MAX_ALLOWED_QUOTE_MINTS: usize = 8;
+
+#[account]
+#[derive(InitSpace)]
+pub struct GlobalConfig {
+    pub authority: Pubkey, // protocol authority allowed to claim protocol fees
+    pub protocol_fee: u16, // share of every swap fee routed to the protocol, in basis points of the fee
+    pub bump: u8, // seed bump for the global config account
+    pub curated: bool, // when set, new pools must pair against one of the allowed quote mints
+    #[max_len(MAX_ALLOWED_QUOTE_MINTS)]
+    pub allowed_quote_mints: Vec<Pubkey>, // at most MAX_ALLOWED_QUOTE_MINTS entries
+    pub max_platform_fee: u16, // cap on the frontend fee a swap can charge, in basis points
+    pub insurance_fee: u16, // share of every swap fee routed to the pool's insurance fund, in basis points of the fee
+    pub fee_target_mint: Pubkey, // mint harvest_fees converts protocol fees into, default when harvesting is off
+    pub reserved: [u8; 32], // room for new fields without resizing the account, always zero
+}
+
+impl GlobalConfig {
+    pub const LEN: usize = 8 + GlobalConfig::INIT_SPACE;
+
+    // Permissionless mode accepts any pair, curated mode needs an allowed mint on either side
+    pub fn allows_pair(&self, mint_x: &Pubkey, mint_y: &Pubkey) -> bool {
+        !self.curated
+            || self.allowed_quote_mints.contains(mint_x)
+            || self.allowed_quote_mints.contains(mint_y)
+    }
+}
@@ -0,0 +1,56 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::INCENTIVE_CLAIM_WINDOW_SECS;
+
+// Third-party reward token emitted linearly to LP stakers between start_ts and end_ts
+#[account]
+#[derive(InitSpace)]
+pub struct Incentive {
+    pub config: Pubkey, // pool whose stakers earn the rewards
+    pub index: u64, // position in the pool's incentive list, part of the seeds
+    pub reward_mint: Pubkey, // token paid out, held in the incentive's reward vault
+    pub funder: Pubkey, // account that deposited the rewards
+    pub amount: u64, // total rewards emitted over the schedule
+    pub start_ts: i64, // emission start
+    pub end_ts: i64, // emission end
+    pub claimed: u64, // rewards already paid out
+    pub bump: u8, // seed bump for the incentive account
+    pub reclaimed: u64, // rewards returned to the funder
+    pub next_reclaim_epoch: u64, // epochs before this one were checked for rewards emitted while nobody was staked
+}
+
+impl Incentive {
+    pub const LEN: usize = 8 + Incentive::INIT_SPACE;
+
+    // Stakers can claim until INCENTIVE_CLAIM_WINDOW_SECS after the schedule ends, what's left then
+    // goes back to the funder
+    pub fn claims_expired(&self, now: i64) -> bool {
+        now >= self.end_ts.saturating_add(INCENTIVE_CLAIM_WINDOW_SECS)
+    }
+
+    // Rewards emitted between from_ts and to_ts
+    pub fn emitted(&self, from_ts: i64, to_ts: i64) -> u64 {
+        let from = from_ts.max(self.start_ts);
+        let to = to_ts.min(self.end_ts);
+
+        if to <= from {
+            return 0;
+        }
+
+        (self.amount as u128 * (to - from) as u128 / (self.end_ts - self.start_ts) as u128) as u64
+    }
+}
+
+// Claim progress of one staker on one incentive
+#[account]
+#[derive(InitSpace)]
+pub struct IncentiveClaim {
+    pub incentive: Pubkey, // incentive being claimed
+    pub owner: Pubkey, // staker claiming it
+    pub next_claim_epoch: u64, // epochs before this one are claimed or forfeited
+    pub bump: u8, // seed bump for the claim account
+}
+
+impl IncentiveClaim {
+    pub const LEN: usize = 8 + IncentiveClaim::INIT_SPACE;
+}
@@ -0,0 +1,15 @@
+use anchor_lang::prelude::*;
+
+// LP tokens a wallet minted while the pool's launch deposit cap was in effect
+#[account]
+#[derive(InitSpace)]
+pub struct LaunchDeposit {
+    pub config: Pubkey, // pool the deposits went into
+    pub owner: Pubkey, // wallet the LP tokens were minted to
+    pub lp_minted: u64, // LP tokens minted during the launch window, withdrawals don't free up room
+    pub bump: u8, // seed bump for the launch deposit account
+}
+
+impl LaunchDeposit {
+    pub const LEN: usize = 8 + LaunchDeposit::INIT_SPACE;
+}
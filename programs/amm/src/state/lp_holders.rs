@@ -0,0 +1,149 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::AmmError;
+
+pub const MAX_LP_HOLDERS: usize = 200;
+
+#[zero_copy]
+pub struct LpHolder {
+    pub owner: Pubkey, // owner of the LP token accounts
+    pub balance: u64, // LP tokens across all of the owner's accounts
+}
+
+// Every LP holder of a pool with holder tracking, kept current by deposits, withdrawals and the LP
+// mint's transfer hook, so reward distributions and snapshots can read balances on-chain
+#[account(zero_copy)]
+pub struct LpHolders {
+    pub mint_lp: Pubkey, // LP mint whose holders are tracked
+    pub count: u32, // holders in use at the front of `holders`
+    pub bump: u8, // seed bump for the registry account
+    pub overflowed: u8, // 1 once an owner was left out for lack of room, balances are incomplete from then on
+    pub padding: [u8; 2], // keeps the holders 8 byte aligned
+    pub holders: [LpHolder; MAX_LP_HOLDERS], // unordered, owners with a zero balance are dropped
+}
+
+impl LpHolders {
+    // discriminator + zero copy layout, which has no implicit padding
+    pub const LEN: usize = 8 + std::mem::size_of::<LpHolders>();
+
+    // A full registry doesn't block deposits and transfers, the new owner is left out and the
+    // registry marked as overflowed instead
+    pub fn credit(&mut self, owner: Pubkey, amount: u64) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+
+        let index = match self.position(&owner) {
+            Some(index) => index,
+            None if (self.count as usize) < MAX_LP_HOLDERS => {
+                self.holders[self.count as usize] = LpHolder { owner, balance: 0 };
+                self.count += 1;
+                self.count as usize - 1
+            },
+            None => {
+                self.overflowed = 1;
+                return Ok(());
+            },
+        };
+
+        let holder = &mut self.holders[index];
+        holder.balance = holder.balance.checked_add(amount).ok_or(AmmError::InvalidAmount)?;
+
+        Ok(())
+    }
+
+    // Once overflowed, owners may hold more than the registry knows of, so their debits only take
+    // what's recorded
+    pub fn debit(&mut self, owner: Pubkey, amount: u64) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+
+        let overflowed = self.overflowed != 0;
+        let Some(index) = self.position(&owner) else {
+            require!(overflowed, AmmError::InsufficientBalance);
+            return Ok(());
+        };
+
+        let holder = &mut self.holders[index];
+        holder.balance = match overflowed {
+            true => holder.balance.saturating_sub(amount),
+            false => holder.balance.checked_sub(amount).ok_or(AmmError::InsufficientBalance)?,
+        };
+
+        // Frees the slot by moving the last holder into it
+        if holder.balance == 0 {
+            self.count -= 1;
+            self.holders[index] = self.holders[self.count as usize];
+        }
+
+        Ok(())
+    }
+
+    fn position(&self, owner: &Pubkey) -> Option<usize> {
+        self.holders[..self.count as usize].iter().position(|holder| holder.owner == *owner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registry() -> LpHolders {
+        LpHolders {
+            mint_lp: Pubkey::default(),
+            count: 0,
+            bump: 255,
+            overflowed: 0,
+            padding: [0; 2],
+            holders: [LpHolder { owner: Pubkey::default(), balance: 0 }; MAX_LP_HOLDERS],
+        }
+    }
+
+    fn owner(index: usize) -> Pubkey {
+        Pubkey::new_from_array([index as u8 + 1; 32])
+    }
+
+    #[test]
+    fn zero_amounts_leave_the_registry_alone() {
+        let mut lp_holders = registry();
+
+        lp_holders.credit(owner(0), 0).unwrap();
+        assert_eq!(lp_holders.count, 0);
+
+        // Not even for unknown owners, which a non-zero debit would reject
+        lp_holders.debit(owner(0), 0).unwrap();
+        assert_eq!(lp_holders.debit(owner(0), 1), Err(AmmError::InsufficientBalance.into()));
+    }
+
+    #[test]
+    fn emptied_balances_free_their_slot() {
+        let mut lp_holders = registry();
+        lp_holders.credit(owner(0), 100).unwrap();
+        lp_holders.credit(owner(1), 50).unwrap();
+
+        lp_holders.debit(owner(0), 100).unwrap();
+
+        assert_eq!(lp_holders.count, 1);
+        assert_eq!(lp_holders.holders[0].owner, owner(1));
+        assert_eq!(lp_holders.holders[0].balance, 50);
+    }
+
+    #[test]
+    fn a_full_registry_leaves_new_owners_out() {
+        let mut lp_holders = registry();
+        for index in 0..MAX_LP_HOLDERS {
+            lp_holders.credit(owner(index), 10).unwrap();
+        }
+
+        // The deposit or transfer goes through without a slot for the new owner
+        lp_holders.credit(owner(MAX_LP_HOLDERS), 10).unwrap();
+        assert_eq!(lp_holders.count as usize, MAX_LP_HOLDERS);
+        assert_eq!(lp_holders.overflowed, 1);
+
+        // and so do its way out and tracked owners moving more than recorded
+        lp_holders.debit(owner(MAX_LP_HOLDERS), 10).unwrap();
+        lp_holders.debit(owner(0), 20).unwrap();
+        assert_eq!(lp_holders.count as usize, MAX_LP_HOLDERS - 1);
+    }
+}
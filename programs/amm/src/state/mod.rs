@@ -1,2 +1,20 @@
 pub mod config;
-pub use config::*;
\ No newline at end of file
+pub mod global_config;
+pub mod observations;
+pub mod epoch;
+pub mod incentive;
+pub mod oracle;
+pub mod launch;
+pub mod withdraw_request;
+pub mod stats;
+pub mod lp_holders;
+pub use config::*;
+pub use global_config::*;
+pub use observations::*;
+pub use epoch::*;
+pub use incentive::*;
+pub use oracle::*;
+pub use launch::*;
+pub use withdraw_request::*;
+pub use stats::*;
+pub use lp_holders::*;
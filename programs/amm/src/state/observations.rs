@@ -0,0 +1,61 @@
+use anchor_lang::prelude::*;
+
+pub const OBSERVATIONS_LEN: usize = 64;
+
+#[zero_copy]
+pub struct Observation {
+    pub timestamp: i64, // unix timestamp the sample was taken at
+    pub padding: [u8; 8], // keeps the u128 fields 16 byte aligned
+    pub price_x_cumulative: u128, // Config::price_x_cumulative at `timestamp`
+    pub price_y_cumulative: u128, // Config::price_y_cumulative at `timestamp`
+}
+
+#[account(zero_copy)]
+pub struct Observations {
+    pub config: Pubkey, // pool the samples belong to
+    pub head: u64, // index of the most recent sample
+    pub count: u64, // number of samples written, capped at OBSERVATIONS_LEN
+    pub samples: [Observation; OBSERVATIONS_LEN], // ring buffer, oldest sample is overwritten first
+}
+
+impl Observations {
+    // discriminator + zero copy layout, which has no implicit padding
+    pub const LEN: usize = 8 + std::mem::size_of::<Observations>();
+
+    // At most one sample per second, later writes in the same second carry the same accumulators
+    pub fn write(&mut self, timestamp: i64, price_x_cumulative: u128, price_y_cumulative: u128) {
+        if self.count > 0 && self.samples[self.head as usize].timestamp == timestamp {
+            return;
+        }
+
+        let next = match self.count {
+            0 => 0,
+            _ => (self.head as usize + 1) % OBSERVATIONS_LEN,
+        };
+
+        self.samples[next] = Observation {
+            timestamp,
+            padding: [0; 8],
+            price_x_cumulative,
+            price_y_cumulative,
+        };
+        self.head = next as u64;
+        self.count = (self.count + 1).min(OBSERVATIONS_LEN as u64);
+    }
+
+    // Newest sample taken at or before `timestamp`, or the oldest one if the buffer doesn't reach back that far
+    pub fn at_or_before(&self, timestamp: i64) -> Option<Observation> {
+        let mut oldest = None;
+
+        for i in 0..self.count as usize {
+            let sample = self.samples[(self.head as usize + OBSERVATIONS_LEN - i) % OBSERVATIONS_LEN];
+
+            if sample.timestamp <= timestamp {
+                return Some(sample);
+            }
+            oldest = Some(sample);
+        }
+
+        oldest
+    }
+}
@@ -0,0 +1,67 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::ORACLE_TWAP_WINDOW_SECS;
+use crate::math::q64_price;
+use crate::state::{Config, Observations};
+
+pub const PRICE_ORACLE_VERSION: u8 = 1;
+
+// Price feed for other programs. The layout is fixed (repr(C), no implicit padding) and only ever
+// grows at the end behind a version bump, so consumers can read it after the 8 byte discriminator
+// without depending on this crate. Prices are Q64.64 fixed point numbers.
+#[account(zero_copy)]
+pub struct PriceOracle {
+    pub config: Pubkey, // pool the feed belongs to
+    pub mint_x: Pubkey, // base token of price_x
+    pub mint_y: Pubkey, // base token of price_y
+    pub spot_price_x: u128, // price of one unit of X in units of Y, from the current reserves
+    pub spot_price_y: u128, // price of one unit of Y in units of X, from the current reserves
+    pub twap_price_x: u128, // time weighted spot_price_x over the last twap_window seconds
+    pub twap_price_y: u128, // time weighted spot_price_y over the last twap_window seconds
+    pub price_x_cumulative: u128, // Config::price_x_cumulative at last_update_ts
+    pub price_y_cumulative: u128, // Config::price_y_cumulative at last_update_ts
+    pub last_update_ts: i64, // unix timestamp of the last update
+    pub last_update_slot: u64, // slot of the last update
+    pub twap_window: i64, // seconds the TWAP actually covers, up to ORACLE_TWAP_WINDOW_SECS
+    pub version: u8, // layout version, PRICE_ORACLE_VERSION
+    pub decimals_x: u8, // decimals of mint_x
+    pub decimals_y: u8, // decimals of mint_y
+    pub padding: [u8; 5], // keeps the size a multiple of 16 bytes
+}
+
+impl PriceOracle {
+    // discriminator + zero copy layout, which has no implicit padding
+    pub const LEN: usize = 8 + std::mem::size_of::<PriceOracle>();
+
+    // Expects the config's accumulators to be up to date and their latest observation written
+    pub fn update(&mut self, config: &Config, observations: &Observations, reserve_x: u64, reserve_y: u64, slot: u64) {
+        let now = config.last_observation_ts;
+
+        // An empty pool has no price, keep the last one
+        let (reserve_x, reserve_y) = config.price_reserves(reserve_x, reserve_y, now);
+        if let (Some(price_x), Some(price_y)) = (q64_price(reserve_x, reserve_y), q64_price(reserve_y, reserve_x)) {
+            self.spot_price_x = price_x;
+            self.spot_price_y = price_y;
+        }
+
+        let (twap_price_x, twap_price_y, twap_window) = match observations.at_or_before(now - ORACLE_TWAP_WINDOW_SECS) {
+            Some(base) if base.timestamp < now => {
+                let elapsed = now - base.timestamp;
+                (
+                    config.price_x_cumulative.wrapping_sub(base.price_x_cumulative) / elapsed as u128,
+                    config.price_y_cumulative.wrapping_sub(base.price_y_cumulative) / elapsed as u128,
+                    elapsed,
+                )
+            }
+            _ => (self.spot_price_x, self.spot_price_y, 0),
+        };
+
+        self.twap_price_x = twap_price_x;
+        self.twap_price_y = twap_price_y;
+        self.twap_window = twap_window;
+        self.price_x_cumulative = config.price_x_cumulative;
+        self.price_y_cumulative = config.price_y_cumulative;
+        self.last_update_ts = now;
+        self.last_update_slot = slot;
+    }
+}
@@ -0,0 +1,53 @@
+use anchor_lang::prelude::*;
+
+pub const MAX_STATS_MINTS: usize = 32;
+
+// Running totals for one mint, in its smallest unit
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, PartialEq, Eq)]
+pub struct MintStats {
+    pub mint: Pubkey,
+    pub volume: u128, // swap input paid in this mint
+    pub protocol_fees: u128, // protocol fees accrued in this mint, claimed or not
+}
+
+// Protocol wide totals across every pool, so a dashboard needs this and the global config only
+#[account]
+#[derive(InitSpace)]
+pub struct ProtocolStats {
+    pub total_pools: u64, // pools initialized and not garbage collected
+    pub total_swaps: u64, // swaps executed in any pool
+    #[max_len(MAX_STATS_MINTS)]
+    pub mints: Vec<MintStats>, // the first MAX_STATS_MINTS mints traded, later ones only count in total_swaps
+    pub bump: u8, // seed bump for the stats account
+    pub reserved: [u8; 64], // room for new fields without resizing the account, always zero
+}
+
+impl ProtocolStats {
+    pub const LEN: usize = 8 + ProtocolStats::INIT_SPACE;
+
+    // Stats never fail the instruction they're recorded in, so totals saturate
+    pub fn record_swap(&mut self, mint_in: Pubkey, amount_in: u64, fee_mint: Pubkey, protocol_fee: u64) {
+        self.total_swaps = self.total_swaps.saturating_add(1);
+
+        if let Some(stats) = self.mint_stats(mint_in) {
+            stats.volume = stats.volume.saturating_add(amount_in as u128);
+        }
+        if let Some(stats) = self.mint_stats(fee_mint) {
+            stats.protocol_fees = stats.protocol_fees.saturating_add(protocol_fee as u128);
+        }
+    }
+
+    // Totals for `mint`, added while there's room
+    fn mint_stats(&mut self, mint: Pubkey) -> Option<&mut MintStats> {
+        let index = match self.mints.iter().position(|stats| stats.mint == mint) {
+            Some(index) => index,
+            None if self.mints.len() < MAX_STATS_MINTS => {
+                self.mints.push(MintStats { mint, volume: 0, protocol_fees: 0 });
+                self.mints.len() - 1
+            }
+            None => return None,
+        };
+
+        self.mints.get_mut(index)
+    }
+}
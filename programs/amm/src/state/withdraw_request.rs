@@ -0,0 +1,85 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::WITHDRAW_CLAIM_WINDOW_SECS;
+use crate::errors::AmmError;
+
+// Pending withdrawal of an LP in a pool with a withdrawal cooldown
+#[account]
+#[derive(InitSpace)]
+pub struct WithdrawRequest {
+    pub config: Pubkey, // pool the LP tokens belong to
+    pub owner: Pubkey, // LP that requested the withdrawal
+    pub lp_amount: u64, // LP tokens that can still be withdrawn under this request
+    pub unlock_ts: i64, // unix timestamp the withdrawal becomes possible at
+    pub bump: u8, // seed bump for the request account
+}
+
+impl WithdrawRequest {
+    pub const LEN: usize = 8 + WithdrawRequest::INIT_SPACE;
+
+    // Takes `lp_amount` off a matured request, for withdrawals and migrations alike. Requests are only
+    // good for WITHDRAW_CLAIM_WINDOW_SECS after unlocking, and only while the LP still holds the
+    // tokens they cover, so one request can't keep the cooldown open indefinitely.
+    pub fn consume(&mut self, lp_amount: u64, balance: u64, now: i64) -> Result<()> {
+        require!(now >= self.unlock_ts, AmmError::TimelockActive);
+        require!(now <= self.unlock_ts + WITHDRAW_CLAIM_WINDOW_SECS, AmmError::WithdrawRequestExpired);
+        require!(self.lp_amount <= balance, AmmError::InsufficientBalance);
+
+        self.lp_amount = self.lp_amount.checked_sub(lp_amount).ok_or(AmmError::WithdrawNotRequested)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request() -> WithdrawRequest {
+        WithdrawRequest {
+            config: Pubkey::default(),
+            owner: Pubkey::default(),
+            lp_amount: 1_000,
+            unlock_ts: 100,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn requests_only_pay_out_after_unlocking() {
+        let mut request = request();
+
+        assert_eq!(request.consume(1, 1_000, 99), Err(AmmError::TimelockActive.into()));
+        assert!(request.consume(1, 1_000, 100).is_ok());
+        assert_eq!(request.lp_amount, 999);
+    }
+
+    #[test]
+    fn requests_go_stale_after_the_claim_window() {
+        let mut request = request();
+        let last = 100 + WITHDRAW_CLAIM_WINDOW_SECS;
+
+        assert_eq!(request.consume(1, 1_000, last + 1), Err(AmmError::WithdrawRequestExpired.into()));
+        assert!(request.consume(1, 1_000, last).is_ok());
+    }
+
+    #[test]
+    fn requests_need_the_lp_tokens_still_held() {
+        let mut request = request();
+
+        // Moving part of the requested LP away voids the request
+        assert_eq!(request.consume(1, 999, 100), Err(AmmError::InsufficientBalance.into()));
+        assert!(request.consume(500, 1_000, 100).is_ok());
+        assert!(request.consume(500, 500, 100).is_ok());
+    }
+
+    #[test]
+    fn requests_cap_the_amount_leaving() {
+        let mut request = request();
+
+        assert_eq!(request.consume(1_001, 1_000, 100), Err(AmmError::WithdrawNotRequested.into()));
+        assert!(request.consume(600, 1_000, 100).is_ok());
+        assert!(request.consume(400, 400, 100).is_ok());
+        assert_eq!(request.consume(1, 0, 100), Err(AmmError::WithdrawNotRequested.into()));
+    }
+}
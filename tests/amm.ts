@@ -5,6 +5,7 @@ import {
   PublicKey, 
   Keypair, 
   SystemProgram,
+  SYSVAR_INSTRUCTIONS_PUBKEY,
 } from "@solana/web3.js";
 import {
   TOKEN_PROGRAM_ID,
@@ -12,6 +13,7 @@ import {
   createMint,
   createAssociatedTokenAccount,
   mintTo,
+  approve,
   getAssociatedTokenAddress,
   getAccount,
   getMint,
@@ -40,9 +42,14 @@ describe("AMM Tests", () => {
   let userAtaLP: PublicKey;
   let configBump: number;
   let lpBump: number;
+  let globalConfig: PublicKey;
+  let protocolStats: PublicKey;
+  let observations: PublicKey;
+  let oracle: PublicKey;
   
   const seed = new BN(12345);
   const fee = 300; // 3% fee in basis points
+  const protocolFee = 1000; // 10% of every swap fee goes to the protocol
   const decimalsX = 6;
   const decimalsY = 9;
 
@@ -91,9 +98,34 @@ describe("AMM Tests", () => {
       program.programId
     );
 
+    [observations] = PublicKey.findProgramAddressSync(
+      [Buffer.from("observations"), config.toBuffer()],
+      program.programId
+    );
+
+    [oracle] = PublicKey.findProgramAddressSync(
+      [Buffer.from("oracle"), config.toBuffer()],
+      program.programId
+    );
+
+    [globalConfig] = PublicKey.findProgramAddressSync(
+      [Buffer.from("global")],
+      program.programId
+    );
+    [protocolStats] = PublicKey.findProgramAddressSync(
+      [Buffer.from("stats")],
+      program.programId
+    );
+
     // Get associated token addresses
-    vaultX = await getAssociatedTokenAddress(mintX, config, true);
-    vaultY = await getAssociatedTokenAddress(mintY, config, true);
+    [vaultX] = PublicKey.findProgramAddressSync(
+      [Buffer.from("vault_x"), config.toBuffer()],
+      program.programId
+    );
+    [vaultY] = PublicKey.findProgramAddressSync(
+      [Buffer.from("vault_y"), config.toBuffer()],
+      program.programId
+    );
     userAtaX = await getAssociatedTokenAddress(mintX, user.publicKey);
     userAtaY = await getAssociatedTokenAddress(mintY, user.publicKey);
     userAtaLP = await getAssociatedTokenAddress(lpMint, user.publicKey);
@@ -108,20 +140,78 @@ describe("AMM Tests", () => {
   });
 
   describe("Initialize", () => {
+    it("Happy Path: Upgrade authority initializes the global config", async () => {
+      const [programData] = PublicKey.findProgramAddressSync(
+        [program.programId.toBuffer()],
+        new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+      );
+
+      await program.methods
+        .initializeGlobal(provider.publicKey, protocolFee)
+        .accounts({
+          admin: provider.publicKey,
+          globalConfig: globalConfig,
+          protocolStats: protocolStats,
+          program: program.programId,
+          programData: programData,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      const globalAccount = await program.account.globalConfig.fetch(globalConfig);
+      expect(globalAccount.authority.toString()).to.equal(provider.publicKey.toString());
+      expect(globalAccount.protocolFee).to.equal(protocolFee);
+      expect(globalAccount.curated).to.be.false;
+    });
+
+    it("Happy Path: Authority manages the curated quote mint allowlist", async () => {
+      await program.methods
+        .addAllowedQuoteMint(mintY)
+        .accounts({ authority: provider.publicKey, globalConfig: globalConfig })
+        .rpc();
+      await program.methods
+        .setCurated(true)
+        .accounts({ authority: provider.publicKey, globalConfig: globalConfig })
+        .rpc();
+
+      let globalAccount = await program.account.globalConfig.fetch(globalConfig);
+      expect(globalAccount.curated).to.be.true;
+      expect(globalAccount.allowedQuoteMints.map((m) => m.toString())).to.deep.equal([mintY.toString()]);
+
+      // Back to permissionless mode for the rest of the suite
+      await program.methods
+        .setCurated(false)
+        .accounts({ authority: provider.publicKey, globalConfig: globalConfig })
+        .rpc();
+      await program.methods
+        .removeAllowedQuoteMint(mintY)
+        .accounts({ authority: provider.publicKey, globalConfig: globalConfig })
+        .rpc();
+
+      globalAccount = await program.account.globalConfig.fetch(globalConfig);
+      expect(globalAccount.curated).to.be.false;
+      expect(globalAccount.allowedQuoteMints).to.be.empty;
+    });
+
     it("Happy Path: Successfully initializes AMM pool", async () => {
       const tx = await program.methods
-        .initialize(seed, fee, null)
+        .initialize(seed, fee, false, false, false, false, new BN(0), null)
         .accounts({
           initializer: payer.publicKey,
+          globalConfig: globalConfig,
+          protocolStats: protocolStats,
           mintX: mintX,
           mintY: mintY,
           mintLp: lpMint,
           vaultX: vaultX,
           vaultY: vaultY,
           config: config,
+          observations: observations,
+          oracle: oracle,
           tokenProgram: TOKEN_PROGRAM_ID,
+          tokenProgramX: TOKEN_PROGRAM_ID,
+          tokenProgramY: TOKEN_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
-          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
         })
         .signers([payer])
         .rpc();
@@ -132,27 +222,46 @@ describe("AMM Tests", () => {
       const configAccount = await program.account.config.fetch(config);
       expect(configAccount.seed.toString()).to.equal(seed.toString());
       expect(configAccount.fee).to.equal(fee);
+      expect(configAccount.feeOnOutput).to.be.false;
+      expect(configAccount.soulbound).to.be.false;
+      // Both test mints keep a freeze authority
+      expect(configAccount.riskFlags).to.equal(0b11);
+      expect(configAccount.cpiPolicy).to.deep.equal({ open: {} });
       expect(configAccount.mintX.toString()).to.equal(mintX.toString());
       expect(configAccount.mintY.toString()).to.equal(mintY.toString());
       expect(configAccount.locked).to.be.false;
+      expect(configAccount.openTime.toNumber()).to.equal(0);
+      expect(configAccount.windowPeriod.toNumber()).to.equal(0);
 
       // Verify LP mint was created
       const lpMintAccount = await getMint(connection, lpMint);
       expect(lpMintAccount.decimals).to.equal(6);
       expect(lpMintAccount.mintAuthority.toString()).to.equal(config.toString());
+      expect(lpMintAccount.freezeAuthority.toString()).to.equal(config.toString());
+
+      // Verify the observation ring buffer was seeded with the creation sample
+      const observationsAccount = await program.account.observations.fetch(observations);
+      expect(observationsAccount.config.toString()).to.equal(config.toString());
+      expect(observationsAccount.count.toNumber()).to.equal(1);
+
+      // Verify the pool was counted in the protocol stats
+      const statsAccount = await program.account.protocolStats.fetch(protocolStats);
+      expect(statsAccount.totalPools.toNumber()).to.equal(1);
     });
   });
 
   describe("Deposit", () => {
     it("Happy Path: Initial deposit (first liquidity provision)", async () => {
-      const lpAmount = new BN(1000 * 10**6); // 1000 LP tokens
       const maxX = new BN(100 * 10**decimalsX); // 100 token X
       const maxY = new BN(200 * 10**decimalsY); // 200 token Y
+      // sqrt(100e6 * 200e9) rounded down, minus the 1_000 locked as minimum liquidity
+      const lpAmount = new BN(4_472_134_954);
 
       const tx = await program.methods
-        .deposit(lpAmount, maxX, maxY)
+        .deposit(maxX, maxY, lpAmount)
         .accounts({
           lpProvider: user.publicKey,
+          owner: user.publicKey,
           mintX: mintX,
           mintY: mintY,
           config: config,
@@ -162,7 +271,10 @@ describe("AMM Tests", () => {
           lpProviderAtaX: userAtaX,
           lpProviderAtaY: userAtaY,
           lpProviderAtaLp: userAtaLP,
+          launchDeposit: null,
           tokenProgram: TOKEN_PROGRAM_ID,
+          tokenProgramX: TOKEN_PROGRAM_ID,
+          tokenProgramY: TOKEN_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
           associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
         })
@@ -183,16 +295,19 @@ describe("AMM Tests", () => {
     });
 
     it("Happy Path: Subsequent deposit (proportional)", async () => {
-      const lpAmount = new BN(500 * 10**6); // 500 LP tokens
-      const maxX = new BN(100 * 10**decimalsX); // Should use proportional amount
-      const maxY = new BN(200 * 10**decimalsY); // Should use proportional amount
+      const maxX = new BN(50 * 10**decimalsX); // Half the pool's X
+      const maxY = new BN(200 * 10**decimalsY); // More than the pool's ratio, only 100 Y is taken
+      // Half the LP tokens outstanding, locked minimum liquidity included
+      const lpAmount = new BN(2_236_067_977);
 
       const userLpBefore = await getAccount(connection, userAtaLP);
+      const userYBefore = await getAccount(connection, userAtaY);
 
       const tx = await program.methods
-        .deposit(lpAmount, maxX, maxY)
+        .deposit(maxX, maxY, lpAmount)
         .accounts({
           lpProvider: user.publicKey,
+          owner: user.publicKey,
           mintX: mintX,
           mintY: mintY,
           config: config,
@@ -202,7 +317,10 @@ describe("AMM Tests", () => {
           lpProviderAtaX: userAtaX,
           lpProviderAtaY: userAtaY,
           lpProviderAtaLp: userAtaLP,
+          launchDeposit: null,
           tokenProgram: TOKEN_PROGRAM_ID,
+          tokenProgramX: TOKEN_PROGRAM_ID,
+          tokenProgramY: TOKEN_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
           associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
         })
@@ -211,21 +329,68 @@ describe("AMM Tests", () => {
 
       console.log("Subsequent deposit tx signature:", tx);
 
-      // Verify LP tokens were minted
+      // Verify LP tokens were minted and only the scarcer side's ratio was taken
       const userLpAfter = await getAccount(connection, userAtaLP);
+      const userYAfter = await getAccount(connection, userAtaY);
       expect(Number(userLpAfter.amount) - Number(userLpBefore.amount)).to.equal(Number(lpAmount));
+      expect(Number(userYBefore.amount) - Number(userYAfter.amount)).to.equal(100 * 10**decimalsY);
     });
 
-    it("Unhappy Path: Fails with zero LP amount", async () => {
-      const lpAmount = new BN(0); // Zero amount
-      const maxX = new BN(100 * 10**decimalsX);
+    it("Happy Path: Delegate deposits on behalf of the owner", async () => {
+      const delegate = Keypair.generate();
+      await connection.requestAirdrop(delegate.publicKey, anchor.web3.LAMPORTS_PER_SOL);
+      await new Promise(resolve => setTimeout(resolve, 1000));
+
+      const maxX = new BN(10 * 10**decimalsX);
+      const maxY = new BN(20 * 10**decimalsY);
+      const minLp = new BN(1);
+
+      // The owner approves the delegate on both token accounts
+      await approve(connection, payer, userAtaX, delegate.publicKey, user, BigInt(maxX.toString()));
+      await approve(connection, payer, userAtaY, delegate.publicKey, user, BigInt(maxY.toString()));
+
+      const userLpBefore = await getAccount(connection, userAtaLP);
+
+      await program.methods
+        .deposit(maxX, maxY, minLp)
+        .accounts({
+          lpProvider: delegate.publicKey,
+          owner: user.publicKey,
+          mintX: mintX,
+          mintY: mintY,
+          config: config,
+          lpMint: lpMint,
+          vaultX: vaultX,
+          vaultY: vaultY,
+          lpProviderAtaX: userAtaX,
+          lpProviderAtaY: userAtaY,
+          lpProviderAtaLp: userAtaLP,
+          launchDeposit: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          tokenProgramX: TOKEN_PROGRAM_ID,
+          tokenProgramY: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        })
+        .signers([delegate])
+        .rpc();
+
+      // LP tokens go to the owner, not the delegate
+      const userLpAfter = await getAccount(connection, userAtaLP);
+      expect(Number(userLpAfter.amount)).to.be.greaterThan(Number(userLpBefore.amount));
+    });
+
+    it("Unhappy Path: Fails with zero deposit amount", async () => {
+      const maxX = new BN(0); // Zero amount
       const maxY = new BN(200 * 10**decimalsY);
+      const minLp = new BN(0);
 
       try {
         await program.methods
-          .deposit(lpAmount, maxX, maxY)
+          .deposit(maxX, maxY, minLp)
           .accounts({
             lpProvider: user.publicKey,
+            owner: user.publicKey,
             mintX: mintX,
             mintY: mintY,
             config: config,
@@ -235,29 +400,33 @@ describe("AMM Tests", () => {
             lpProviderAtaX: userAtaX,
             lpProviderAtaY: userAtaY,
             lpProviderAtaLp: userAtaLP,
+            launchDeposit: null,
             tokenProgram: TOKEN_PROGRAM_ID,
+            tokenProgramX: TOKEN_PROGRAM_ID,
+            tokenProgramY: TOKEN_PROGRAM_ID,
             systemProgram: SystemProgram.programId,
             associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
           })
           .signers([user])
           .rpc();
 
-        expect.fail("Should have failed with zero LP amount");
+        expect.fail("Should have failed with zero deposit amount");
       } catch (error) {
         expect(error.message).to.include("InvalidAmount");
       }
     });
 
-    it("Unhappy Path: Fails with insufficient max amounts", async () => {
-      const lpAmount = new BN(100 * 10**6);
+    it("Unhappy Path: Fails when the LP minted is below the minimum", async () => {
       const maxX = new BN(1); // Very small max amount
       const maxY = new BN(1); // Very small max amount
+      const minLp = new BN(100 * 10**6);
 
       try {
         await program.methods
-          .deposit(lpAmount, maxX, maxY)
+          .deposit(maxX, maxY, minLp)
           .accounts({
             lpProvider: user.publicKey,
+            owner: user.publicKey,
             mintX: mintX,
             mintY: mintY,
             config: config,
@@ -267,17 +436,19 @@ describe("AMM Tests", () => {
             lpProviderAtaX: userAtaX,
             lpProviderAtaY: userAtaY,
             lpProviderAtaLp: userAtaLP,
+            launchDeposit: null,
             tokenProgram: TOKEN_PROGRAM_ID,
+            tokenProgramX: TOKEN_PROGRAM_ID,
+            tokenProgramY: TOKEN_PROGRAM_ID,
             systemProgram: SystemProgram.programId,
             associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
           })
           .signers([user])
           .rpc();
 
-        expect.fail("Should have failed with insufficient max amounts");
+        expect.fail("Should have failed below the minimum LP amount");
       } catch (error) {
-        console.log("Expected insufficient amounts error:", error.message);
-        // Accept any simulation failure as the contract logic may prevent this
+        expect(error.message).to.include("SlippageExceeded");
       }
     });
   });
@@ -292,10 +463,15 @@ describe("AMM Tests", () => {
       const userYBefore = await getAccount(connection, userAtaY);
 
       const tx = await program.methods
-        .swap(isX, amount, minOut)
+        .swap(isX, amount, minOut, 0)
         .accounts({
           user: user.publicKey,
+          owner: user.publicKey,
+          globalConfig: globalConfig,
+          protocolStats: protocolStats,
           config: config,
+          observations: observations,
+          oracle: oracle,
           mintLp: lpMint,
           mintX: mintX,
           mintY: mintY,
@@ -303,7 +479,12 @@ describe("AMM Tests", () => {
           vaultY: vaultY,
           userAtaX: userAtaX,
           userAtaY: userAtaY,
+          platformFeeReceiver: null,
+          discountAccount: null,
+          instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
           tokenProgram: TOKEN_PROGRAM_ID,
+          tokenProgramX: TOKEN_PROGRAM_ID,
+          tokenProgramY: TOKEN_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
           associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
         })
@@ -320,6 +501,25 @@ describe("AMM Tests", () => {
       
       // Verify Y tokens were received (should be > 0)
       expect(Number(userYAfter.amount) > Number(userYBefore.amount)).to.be.true;
+
+      // Verify the trade was recorded in the pool state
+      const configAccount = await program.account.config.fetch(config);
+      expect(configAccount.lastTradeIsX).to.be.true;
+      expect(configAccount.lastTradeSlot.toNumber() > 0).to.be.true;
+      expect(configAccount.lastTradePrice.gtn(0)).to.be.true;
+
+      // Verify the oracle published the post-trade spot price
+      const oracleAccount = await program.account.priceOracle.fetch(oracle);
+      expect(oracleAccount.config.toString()).to.equal(config.toString());
+      expect(oracleAccount.version).to.equal(1);
+      expect(oracleAccount.spotPriceX.gtn(0)).to.be.true;
+      expect(oracleAccount.lastUpdateSlot.toNumber()).to.equal(configAccount.lastTradeSlot.toNumber());
+
+      // Verify the protocol stats picked up the volume in X
+      const statsAccount = await program.account.protocolStats.fetch(protocolStats);
+      const statsX = statsAccount.mints.find((m) => m.mint.equals(mintX));
+      expect(statsAccount.totalSwaps.toNumber()).to.equal(1);
+      expect(statsX.volume.toString()).to.equal(amount.toString());
     });
 
     it("Happy Path: Swap Y for X", async () => {
@@ -331,10 +531,15 @@ describe("AMM Tests", () => {
       const userYBefore = await getAccount(connection, userAtaY);
 
       const tx = await program.methods
-        .swap(isX, amount, minOut)
+        .swap(isX, amount, minOut, 0)
         .accounts({
           user: user.publicKey,
+          owner: user.publicKey,
+          globalConfig: globalConfig,
+          protocolStats: protocolStats,
           config: config,
+          observations: observations,
+          oracle: oracle,
           mintLp: lpMint,
           mintX: mintX,
           mintY: mintY,
@@ -342,7 +547,12 @@ describe("AMM Tests", () => {
           vaultY: vaultY,
           userAtaX: userAtaX,
           userAtaY: userAtaY,
+          platformFeeReceiver: null,
+          discountAccount: null,
+          instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
           tokenProgram: TOKEN_PROGRAM_ID,
+          tokenProgramX: TOKEN_PROGRAM_ID,
+          tokenProgramY: TOKEN_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
           associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
         })
@@ -361,6 +571,182 @@ describe("AMM Tests", () => {
       expect(Number(userXAfter.amount) > Number(userXBefore.amount)).to.be.true;
     });
 
+    it("Happy Path: Exact-output swap buys exactly the requested amount", async () => {
+      const amountOut = new BN(1 * 10**decimalsY); // 1 token Y
+      const maxIn = new BN(10 * 10**decimalsX);
+
+      const userXBefore = await getAccount(connection, userAtaX);
+      const userYBefore = await getAccount(connection, userAtaY);
+
+      await program.methods
+        .swapExactOut(true, amountOut, maxIn, false)
+        .accounts({
+          user: user.publicKey,
+          owner: user.publicKey,
+          globalConfig: globalConfig,
+          protocolStats: protocolStats,
+          config: config,
+          observations: observations,
+          oracle: oracle,
+          mintLp: lpMint,
+          mintX: mintX,
+          mintY: mintY,
+          vaultX: vaultX,
+          vaultY: vaultY,
+          userAtaX: userAtaX,
+          userAtaY: userAtaY,
+          platformFeeReceiver: null,
+          discountAccount: null,
+          instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          tokenProgramX: TOKEN_PROGRAM_ID,
+          tokenProgramY: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        })
+        .signers([user])
+        .rpc();
+
+      const userXAfter = await getAccount(connection, userAtaX);
+      const userYAfter = await getAccount(connection, userAtaY);
+
+      expect(Number(userYAfter.amount) - Number(userYBefore.amount)).to.equal(Number(amountOut));
+      const paid = Number(userXBefore.amount) - Number(userXAfter.amount);
+      expect(paid > 0 && paid <= Number(maxIn)).to.be.true;
+    });
+
+    it("Happy Path: Exact-output swap with allow_partial fills what max_in covers", async () => {
+      const amountOut = new BN(100 * 10**decimalsY); // Far more than 1 token X buys
+      const maxIn = new BN(1 * 10**decimalsX);
+
+      const userXBefore = await getAccount(connection, userAtaX);
+      const userYBefore = await getAccount(connection, userAtaY);
+
+      await program.methods
+        .swapExactOut(true, amountOut, maxIn, true)
+        .accounts({
+          user: user.publicKey,
+          owner: user.publicKey,
+          globalConfig: globalConfig,
+          protocolStats: protocolStats,
+          config: config,
+          observations: observations,
+          oracle: oracle,
+          mintLp: lpMint,
+          mintX: mintX,
+          mintY: mintY,
+          vaultX: vaultX,
+          vaultY: vaultY,
+          userAtaX: userAtaX,
+          userAtaY: userAtaY,
+          platformFeeReceiver: null,
+          discountAccount: null,
+          instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          tokenProgramX: TOKEN_PROGRAM_ID,
+          tokenProgramY: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        })
+        .signers([user])
+        .rpc();
+
+      const userXAfter = await getAccount(connection, userAtaX);
+      const userYAfter = await getAccount(connection, userAtaY);
+
+      // All of max_in is spent on part of the request
+      expect(Number(userXBefore.amount) - Number(userXAfter.amount)).to.equal(Number(maxIn));
+      const received = Number(userYAfter.amount) - Number(userYBefore.amount);
+      expect(received > 0 && received < Number(amountOut)).to.be.true;
+    });
+
+    it("Unhappy Path: Exact-output swap fails beyond max_in without allow_partial", async () => {
+      try {
+        await program.methods
+          .swapExactOut(true, new BN(100 * 10**decimalsY), new BN(1 * 10**decimalsX), false)
+          .accounts({
+            user: user.publicKey,
+            owner: user.publicKey,
+            globalConfig: globalConfig,
+            protocolStats: protocolStats,
+            config: config,
+            observations: observations,
+            oracle: oracle,
+            mintLp: lpMint,
+            mintX: mintX,
+            mintY: mintY,
+            vaultX: vaultX,
+            vaultY: vaultY,
+            userAtaX: userAtaX,
+            userAtaY: userAtaY,
+            platformFeeReceiver: null,
+            discountAccount: null,
+            instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            tokenProgramX: TOKEN_PROGRAM_ID,
+            tokenProgramY: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+            associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          })
+          .signers([user])
+          .rpc();
+
+        expect.fail("Should have failed without enough input");
+      } catch (error) {
+        expect(error.message).to.include("SlippageExceeded");
+      }
+    });
+
+    it("Happy Path: Swap charges a platform fee to the frontend", async () => {
+      const platformFee = 50; // 0.5%
+      const amount = new BN(10 * 10**decimalsX);
+      const frontend = Keypair.generate();
+      const receiver = await createAssociatedTokenAccount(connection, payer, mintX, frontend.publicKey);
+
+      await program.methods
+        .setMaxPlatformFee(100)
+        .accounts({ authority: provider.publicKey, globalConfig: globalConfig })
+        .rpc();
+
+      const userXBefore = await getAccount(connection, userAtaX);
+
+      await program.methods
+        .swap(true, amount, new BN(1), platformFee)
+        .accounts({
+          user: user.publicKey,
+          owner: user.publicKey,
+          globalConfig: globalConfig,
+          protocolStats: protocolStats,
+          config: config,
+          observations: observations,
+          oracle: oracle,
+          mintLp: lpMint,
+          mintX: mintX,
+          mintY: mintY,
+          vaultX: vaultX,
+          vaultY: vaultY,
+          userAtaX: userAtaX,
+          userAtaY: userAtaY,
+          platformFeeReceiver: receiver,
+          discountAccount: null,
+          instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          tokenProgramX: TOKEN_PROGRAM_ID,
+          tokenProgramY: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        })
+        .signers([user])
+        .rpc();
+
+      const userXAfter = await getAccount(connection, userAtaX);
+      const receiverAccount = await getAccount(connection, receiver);
+
+      // The user spends exactly `amount`, of which the platform fee goes to the frontend
+      expect(Number(userXBefore.amount) - Number(userXAfter.amount)).to.equal(Number(amount));
+      expect(receiverAccount.amount.toString()).to.equal(amount.muln(platformFee).divn(10_000).toString());
+    });
+
     it("Unhappy Path: Fails with zero amount", async () => {
       const isX = true;
       const amount = new BN(0); // Zero amount
@@ -368,10 +754,16 @@ describe("AMM Tests", () => {
 
       try {
         await program.methods
-          .swap(isX, amount, minOut)
+          .swap(isX, amount, minOut, 0)
           .accounts({
             user: user.publicKey,
+            owner: user.publicKey,
+            globalConfig: globalConfig,
+            protocolStats: protocolStats,
             config: config,
+            observations: observations,
+            oracle: oracle,
+          oracle: oracle,
             mintLp: lpMint,
             mintX: mintX,
             mintY: mintY,
@@ -379,7 +771,12 @@ describe("AMM Tests", () => {
             vaultY: vaultY,
             userAtaX: userAtaX,
             userAtaY: userAtaY,
+            platformFeeReceiver: null,
+            discountAccount: null,
+            instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
             tokenProgram: TOKEN_PROGRAM_ID,
+            tokenProgramX: TOKEN_PROGRAM_ID,
+            tokenProgramY: TOKEN_PROGRAM_ID,
             systemProgram: SystemProgram.programId,
             associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
           })
@@ -399,10 +796,16 @@ describe("AMM Tests", () => {
 
       try {
         await program.methods
-          .swap(isX, amount, minOut)
+          .swap(isX, amount, minOut, 0)
           .accounts({
             user: user.publicKey,
+            owner: user.publicKey,
+            globalConfig: globalConfig,
+            protocolStats: protocolStats,
             config: config,
+            observations: observations,
+            oracle: oracle,
+          oracle: oracle,
             mintLp: lpMint,
             mintX: mintX,
             mintY: mintY,
@@ -410,7 +813,12 @@ describe("AMM Tests", () => {
             vaultY: vaultY,
             userAtaX: userAtaX,
             userAtaY: userAtaY,
+            platformFeeReceiver: null,
+            discountAccount: null,
+            instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
             tokenProgram: TOKEN_PROGRAM_ID,
+            tokenProgramX: TOKEN_PROGRAM_ID,
+            tokenProgramY: TOKEN_PROGRAM_ID,
             systemProgram: SystemProgram.programId,
             associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
           })
@@ -425,6 +833,159 @@ describe("AMM Tests", () => {
     });
   });
 
+  describe("Views", () => {
+    it("Happy Path: Virtual price matches reserves per LP token", async () => {
+      const virtualPrice = await program.methods
+        .getVirtualPrice()
+        .accounts({
+          mintX: mintX,
+          mintY: mintY,
+          config: config,
+          mintLp: lpMint,
+          vaultX: vaultX,
+          vaultY: vaultY,
+        })
+        .view();
+
+      const configAccount = await program.account.config.fetch(config);
+      const lpMintAccount = await getMint(connection, lpMint);
+      const vaultXAccount = await getAccount(connection, vaultX);
+      // Redemption value covers the reserves plus the LP fee balance
+      const redeemableX = new BN(vaultXAccount.amount.toString()).sub(configAccount.protocolFeesX);
+      const expectedX = redeemableX.mul(new BN(10 ** 6)).div(new BN(lpMintAccount.supply.toString()));
+
+      expect(virtualPrice.x.toString()).to.equal(expectedX.toString());
+      expect(virtualPrice.y.toNumber() > 0).to.be.true;
+    });
+
+    it("Happy Path: Pool info aggregates pool state", async () => {
+      const poolInfo = await program.methods
+        .getPoolInfo()
+        .accounts({
+          mintX: mintX,
+          mintY: mintY,
+          config: config,
+          mintLp: lpMint,
+          vaultX: vaultX,
+          vaultY: vaultY,
+        })
+        .view();
+
+      const configAccount = await program.account.config.fetch(config);
+      const lpMintAccount = await getMint(connection, lpMint);
+
+      expect(poolInfo.lpSupply.toString()).to.equal(lpMintAccount.supply.toString());
+      expect(poolInfo.fee).to.equal(fee);
+      expect(poolInfo.locked).to.be.false;
+      expect(poolInfo.protocolFeesX.toString()).to.equal(configAccount.protocolFeesX.toString());
+      expect(poolInfo.lpFeesX.toString()).to.equal(configAccount.lpFeesX.toString());
+      expect(poolInfo.lpFeesX.gtn(0)).to.be.true;
+      expect(poolInfo.spotPrice.gtn(0)).to.be.true;
+    });
+  });
+
+  describe("Protocol Fees", () => {
+    it("Happy Path: Authority claims part of the accrued protocol fees", async () => {
+      const receiverX = await createAssociatedTokenAccount(connection, payer, mintX, provider.publicKey);
+      const receiverY = await createAssociatedTokenAccount(connection, payer, mintY, provider.publicKey);
+
+      const configBefore = await program.account.config.fetch(config);
+      expect(configBefore.protocolFeesX.toNumber() > 0).to.be.true;
+      const claimX = configBefore.protocolFeesX.divn(2);
+
+      await program.methods
+        .claimProtocolFees(claimX, new BN(0))
+        .accounts({
+          authority: provider.publicKey,
+          globalConfig: globalConfig,
+          mintX: mintX,
+          mintY: mintY,
+          config: config,
+          vaultX: vaultX,
+          vaultY: vaultY,
+          receiverX: receiverX,
+          receiverY: receiverY,
+          tokenProgramX: TOKEN_PROGRAM_ID,
+          tokenProgramY: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+      const configAfter = await program.account.config.fetch(config);
+      const receiverXAccount = await getAccount(connection, receiverX);
+      expect(receiverXAccount.amount.toString()).to.equal(claimX.toString());
+      expect(configAfter.protocolFeesX.toString()).to.equal(configBefore.protocolFeesX.sub(claimX).toString());
+      expect(configAfter.protocolFeesY.toString()).to.equal(configBefore.protocolFeesY.toString());
+    });
+
+    it("Unhappy Path: Fails to claim more than accrued", async () => {
+      const configAccount = await program.account.config.fetch(config);
+      const receiverX = await getAssociatedTokenAddress(mintX, provider.publicKey);
+      const receiverY = await getAssociatedTokenAddress(mintY, provider.publicKey);
+
+      try {
+        await program.methods
+          .claimProtocolFees(configAccount.protocolFeesX.addn(1), new BN(0))
+          .accounts({
+            authority: provider.publicKey,
+            globalConfig: globalConfig,
+            mintX: mintX,
+            mintY: mintY,
+            config: config,
+            vaultX: vaultX,
+            vaultY: vaultY,
+            receiverX: receiverX,
+            receiverY: receiverY,
+            tokenProgramX: TOKEN_PROGRAM_ID,
+            tokenProgramY: TOKEN_PROGRAM_ID,
+          })
+          .rpc();
+
+        expect.fail("Should have failed claiming more than accrued");
+      } catch (error) {
+        expect(error.message).to.include("ExceedsAccruedFees");
+      }
+    });
+
+    it("Unhappy Path: Fails to harvest fees before the oracle TWAP covers its window", async () => {
+      const treasuryY = await getAssociatedTokenAddress(mintY, provider.publicKey);
+
+      await program.methods
+        .setFeeTargetMint(mintY)
+        .accounts({ authority: provider.publicKey, globalConfig: globalConfig })
+        .rpc();
+
+      try {
+        await program.methods
+          .harvestFees()
+          .accounts({
+            caller: user.publicKey,
+            globalConfig: globalConfig,
+            mintX: mintX,
+            mintY: mintY,
+            config: config,
+            observations: observations,
+            oracle: oracle,
+            vaultX: vaultX,
+            vaultY: vaultY,
+            treasury: treasuryY,
+            tokenProgramX: TOKEN_PROGRAM_ID,
+            tokenProgramY: TOKEN_PROGRAM_ID,
+          })
+          .signers([user])
+          .rpc();
+
+        expect.fail("Should have failed harvesting against a young oracle");
+      } catch (error) {
+        expect(error.message).to.include("OracleUnavailable");
+      }
+
+      await program.methods
+        .setFeeTargetMint(PublicKey.default)
+        .accounts({ authority: provider.publicKey, globalConfig: globalConfig })
+        .rpc();
+    });
+  });
+
   describe("Withdraw", () => {
     it("Happy Path: Partial withdrawal", async () => {
       // Get current LP balance
@@ -456,7 +1017,10 @@ describe("AMM Tests", () => {
           lpProviderAtaX: userAtaX,
           lpProviderAtaY: userAtaY,
           lpProviderAtaLp: userAtaLP,
+          withdrawRequest: null,
           tokenProgram: TOKEN_PROGRAM_ID,
+          tokenProgramX: TOKEN_PROGRAM_ID,
+          tokenProgramY: TOKEN_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
           associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
         })
@@ -492,7 +1056,10 @@ describe("AMM Tests", () => {
             lpProviderAtaX: userAtaX,
             lpProviderAtaY: userAtaY,
             lpProviderAtaLp: userAtaLP,
+            withdrawRequest: null,
             tokenProgram: TOKEN_PROGRAM_ID,
+            tokenProgramX: TOKEN_PROGRAM_ID,
+            tokenProgramY: TOKEN_PROGRAM_ID,
             systemProgram: SystemProgram.programId,
             associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
           })
@@ -506,11 +1073,178 @@ describe("AMM Tests", () => {
     });
   });
 
+  describe("Withdraw All", () => {
+    it("Happy Path: Burns the whole LP balance and closes the LP account", async () => {
+      const lamportsBefore = await connection.getBalance(user.publicKey);
+
+      const tx = await program.methods
+        .withdrawAll(new BN(1), new BN(1))
+        .accounts({
+          lpProvider: user.publicKey,
+          mintX: mintX,
+          mintY: mintY,
+          config: config,
+          mintLp: lpMint,
+          vaultX: vaultX,
+          vaultY: vaultY,
+          lpProviderAtaX: userAtaX,
+          lpProviderAtaY: userAtaY,
+          lpProviderAtaLp: userAtaLP,
+          withdrawRequest: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          tokenProgramX: TOKEN_PROGRAM_ID,
+          tokenProgramY: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        })
+        .signers([user])
+        .rpc();
+
+      console.log("Withdraw all tx signature:", tx);
+
+      // LP account is gone and its rent went back to the provider
+      expect(await connection.getAccountInfo(userAtaLP)).to.be.null;
+      expect(await connection.getBalance(user.publicKey) > lamportsBefore).to.be.true;
+
+      const lpMintAccount = await getMint(connection, lpMint);
+      expect(lpMintAccount.supply.toString()).to.equal("0");
+    });
+  });
+
   describe("Edge Cases", () => {
     it("Should handle configuration properly", async () => {
       const configAccount = await program.account.config.fetch(config);
       expect(configAccount.authority).to.be.null; // No authority set in our test
       expect(configAccount.locked).to.be.false;
     });
+
+    it("Unhappy Path: Fails to garbage collect a pool still holding tokens", async () => {
+      const treasuryX = await getAssociatedTokenAddress(mintX, provider.publicKey);
+      const treasuryY = await getAssociatedTokenAddress(mintY, provider.publicKey);
+      const [crankVault] = PublicKey.findProgramAddressSync([Buffer.from("crank"), config.toBuffer()], program.programId);
+      const [lpHolders] = PublicKey.findProgramAddressSync([Buffer.from("lp_holders"), lpMint.toBuffer()], program.programId);
+      const [extraAccountMetas] = PublicKey.findProgramAddressSync(
+        [Buffer.from("extra-account-metas"), lpMint.toBuffer()],
+        program.programId
+      );
+      const stakeVault = await getAssociatedTokenAddress(lpMint, config, true);
+
+      try {
+        await program.methods
+          .gcPool()
+          .accounts({
+            caller: user.publicKey,
+            globalConfig: globalConfig,
+            treasury: provider.publicKey,
+            protocolStats: protocolStats,
+            mintX: mintX,
+            mintY: mintY,
+            config: config,
+            observations: observations,
+            oracle: oracle,
+            mintLp: lpMint,
+            vaultX: vaultX,
+            vaultY: vaultY,
+            treasuryX: treasuryX,
+            treasuryY: treasuryY,
+            crankVault: crankVault,
+            lpHolders: lpHolders,
+            extraAccountMetas: extraAccountMetas,
+            stakeVault: stakeVault,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            tokenProgramX: TOKEN_PROGRAM_ID,
+            tokenProgramY: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([user])
+          .rpc();
+
+        expect.fail("Should have failed collecting a pool with unclaimed fees");
+      } catch (error) {
+        expect(error.message).to.include("PoolNotEmpty");
+      }
+    });
+
+    it("Unhappy Path: Fails to deploy reserves without a strategy", async () => {
+      try {
+        await program.methods
+          .deployIdle(true, new BN(1 * 10**decimalsX))
+          .accounts({
+            signer: user.publicKey,
+            mintX: mintX,
+            mintY: mintY,
+            config: config,
+            vaultX: vaultX,
+            vaultY: vaultY,
+            strategyAccount: userAtaX,
+            tokenProgramX: TOKEN_PROGRAM_ID,
+            tokenProgramY: TOKEN_PROGRAM_ID,
+          })
+          .signers([user])
+          .rpc();
+
+        expect.fail("Should have failed deploying without a strategy");
+      } catch (error) {
+        expect(error.message).to.include("InvalidConfig");
+      }
+    });
+  });
+
+  describe("Devnet Seeding", () => {
+    // Only built with `anchor build -- --features devnet`
+    const devnet = program.idl.instructions.some((ix) => ix.name === "seedTestPool");
+
+    (devnet ? it : it.skip)("Happy Path: Seeds a test pool in one instruction", async () => {
+      const testMintX = Keypair.generate();
+      const testMintY = Keypair.generate();
+      const testSeed = new BN(777);
+      const reserveX = new BN(1_000 * 10**6);
+      const reserveY = new BN(4_000 * 10**6);
+
+      const [testConfig] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("config"),
+          testMintX.publicKey.toBuffer(),
+          testMintY.publicKey.toBuffer(),
+          testSeed.toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      );
+      const pda = (label: string) =>
+        PublicKey.findProgramAddressSync([Buffer.from(label), testConfig.toBuffer()], program.programId)[0];
+      const testVaultX = pda("vault_x");
+      const testVaultY = pda("vault_y");
+      const testLpMint = pda("lp");
+
+      await (program.methods as any)
+        .seedTestPool(testSeed, fee, reserveX, reserveY)
+        .accounts({
+          payer: provider.publicKey,
+          globalConfig: globalConfig,
+          protocolStats: protocolStats,
+          mintX: testMintX.publicKey,
+          mintY: testMintY.publicKey,
+          payerAtaX: await getAssociatedTokenAddress(testMintX.publicKey, provider.publicKey),
+          payerAtaY: await getAssociatedTokenAddress(testMintY.publicKey, provider.publicKey),
+          config: testConfig,
+          mintLp: testLpMint,
+          vaultX: testVaultX,
+          vaultY: testVaultY,
+          observations: pda("observations"),
+          oracle: pda("oracle"),
+          payerAtaLp: await getAssociatedTokenAddress(testLpMint, provider.publicKey),
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          ammProgram: program.programId,
+        })
+        .signers([testMintX, testMintY])
+        .rpc();
+
+      expect((await getAccount(connection, testVaultX)).amount.toString()).to.equal(reserveX.toString());
+      expect((await getAccount(connection, testVaultY)).amount.toString()).to.equal(reserveY.toString());
+      // sqrt(1_000e6 * 4_000e6)
+      expect((await getMint(connection, testLpMint)).supply.toString()).to.equal((2_000 * 10**6).toString());
+    });
   });
 });
\ No newline at end of file